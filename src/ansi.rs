@@ -0,0 +1,194 @@
+use super::{
+    color::BASIC,
+    style::{none, rgb, Color, Modifier, Style},
+};
+
+/// Piece of text containing escape sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    /// Plain text
+    Text(&'a str),
+    /// Control sequence with its parameters and final byte
+    Csi(&'a str, char),
+    /// Any other escape sequence
+    Escape,
+}
+
+/// Split text into plain text and escape sequences
+fn tokens(s: &str) -> impl Iterator<Item = Token<'_>> {
+    let mut rest = s;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        if let Some(i) = rest.find('\x1b').filter(|i| *i > 0) {
+            let (text, tail) = rest.split_at(i);
+            rest = tail;
+            return Some(Token::Text(text));
+        } else if !rest.starts_with('\x1b') {
+            return Some(Token::Text(std::mem::take(&mut rest)));
+        }
+        let seq = &rest[1..];
+        let (token, len) = match seq.chars().next() {
+            // CSI: parameters and intermediates followed by a final byte
+            Some('[') => match seq[1..].find(|c| ('\x40'..='\x7e').contains(&c)) {
+                Some(end) => {
+                    let f = seq[1 + end..].chars().next().unwrap();
+                    (Token::Csi(&seq[1..1 + end], f), 2 + end + 1)
+                }
+                None => (Token::Escape, rest.len()),
+            },
+            // OSC: terminated by BEL or ST
+            Some(']') => {
+                let end = (seq.find('\x07').map(|i| i + 1))
+                    .into_iter()
+                    .chain(seq.find("\x1b\\").map(|i| i + 2))
+                    .min()
+                    .unwrap_or(seq.len());
+                (Token::Escape, 1 + end)
+            }
+            Some(c) => (Token::Escape, 1 + c.len_utf8()),
+            None => (Token::Escape, 1),
+        };
+        rest = &rest[len..];
+        Some(token)
+    })
+}
+
+/// Remove escape sequences such as colors, keeping the plain text
+pub fn strip_ansi(s: &str) -> String {
+    tokens(s)
+        .filter_map(|t| match t {
+            Token::Text(text) => Some(text),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Split text colored with SGR escape sequences into styled spans, other sequences being
+/// skipped
+pub fn parse_ansi(s: &str) -> Vec<(String, Style)> {
+    let mut spans: Vec<(String, Style)> = Vec::new();
+    let mut style = none();
+    for token in tokens(s) {
+        match token {
+            Token::Text(text) => match spans.last_mut() {
+                Some((prev, prev_style)) if *prev_style == style => prev.push_str(text),
+                _ => spans.push((text.to_string(), style)),
+            },
+            Token::Csi(params, 'm') => style = apply_sgr(style, params),
+            _ => {}
+        }
+    }
+    spans
+}
+
+/// Apply SGR parameters to a style, resets leaving colors unset
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    // An empty parameter is a 0, an invalid one is skipped
+    let mut codes = params.split(';').map(|p| match p {
+        "" => Some(0),
+        p => p.parse::<u8>().ok(),
+    });
+    while let Some(code) = codes.next() {
+        let Some(code) = code else { continue };
+        match code {
+            0 => style = none(),
+            1 => style.modifier.insert(Modifier::BOLD),
+            2 => style.modifier.insert(Modifier::DIM),
+            3 => style.modifier.insert(Modifier::ITALIC),
+            4 => style.modifier.insert(Modifier::UNDERLINED),
+            5 => style.modifier.insert(Modifier::BLINK),
+            6 => style.modifier.insert(Modifier::RAPID_BLINK),
+            7 => style.modifier.insert(Modifier::REVERSED),
+            8 => style.modifier.insert(Modifier::HIDDEN),
+            9 => style.modifier.insert(Modifier::CROSSED_OUT),
+            22 => style.modifier.remove(Modifier::BOLD | Modifier::DIM),
+            23 => style.modifier.remove(Modifier::ITALIC),
+            24 => style.modifier.remove(Modifier::UNDERLINED),
+            25 => style
+                .modifier
+                .remove(Modifier::BLINK | Modifier::RAPID_BLINK),
+            27 => style.modifier.remove(Modifier::REVERSED),
+            28 => style.modifier.remove(Modifier::HIDDEN),
+            29 => style.modifier.remove(Modifier::CROSSED_OUT),
+            30..=37 => style.fg = Some(BASIC[code as usize - 30]),
+            38 => style.fg = extended_color(&mut codes).or(style.fg),
+            39 => style.fg = None,
+            40..=47 => style.bg = Some(BASIC[code as usize - 40]),
+            48 => style.bg = extended_color(&mut codes).or(style.bg),
+            49 => style.bg = None,
+            90..=97 => style.fg = Some(BASIC[code as usize - 90 + 8]),
+            100..=107 => style.bg = Some(BASIC[code as usize - 100 + 8]),
+            _ => {}
+        }
+    }
+    style
+}
+
+/// Palette `5;n` or truecolor `2;r;g;b` color following a 38 or 48 code
+fn extended_color(codes: &mut impl Iterator<Item = Option<u8>>) -> Option<Color> {
+    let mut next = || codes.next().flatten();
+    match next()? {
+        5 => Some(Color::AnsiValue(next()?)),
+        2 => Some(rgb(next()?, next()?, next()?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip() {
+        assert_eq!(strip_ansi("\x1b[31mred\x1b[0m"), "red");
+        assert_eq!(strip_ansi("a\x1b[38;2;1;2;3mb\x1b[Kc"), "abc");
+        assert_eq!(
+            strip_ansi("\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x07"),
+            "link"
+        );
+        assert_eq!(strip_ansi("plain コン"), "plain コン");
+        assert_eq!(strip_ansi("cut\x1b[3"), "cut");
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            parse_ansi("\x1b[31mred\x1b[0m plain"),
+            vec![
+                ("red".to_string(), none().fg(Color::DarkRed)),
+                (" plain".to_string(), none())
+            ]
+        );
+        assert_eq!(
+            parse_ansi("\x1b[1;38;2;1;2;3;104ma\x1b[Kb\x1b[22;39mc\x1b[49m"),
+            vec![
+                (
+                    "ab".to_string(),
+                    none().bold().fg(rgb(1, 2, 3)).bg(Color::Blue)
+                ),
+                ("c".to_string(), none().bg(Color::Blue))
+            ]
+        );
+        assert_eq!(
+            parse_ansi("\x1b[38;5;196;3mx\x1b[23;9my"),
+            vec![
+                ("x".to_string(), none().fg(Color::AnsiValue(196)).italic()),
+                (
+                    "y".to_string(),
+                    none().fg(Color::AnsiValue(196)).crossed_out()
+                )
+            ]
+        );
+        // Invalid parameters are skipped rather than resetting
+        assert_eq!(
+            parse_ansi("\x1b[1mA\x1b[1000;3mB\x1b[;4mC"),
+            vec![
+                ("A".to_string(), none().bold()),
+                ("B".to_string(), none().bold().italic()),
+                ("C".to_string(), none().underline())
+            ]
+        );
+    }
+}
@@ -0,0 +1,463 @@
+use std::io;
+#[cfg(feature = "crossterm")]
+use std::io::{BufWriter, Write};
+
+use bitflags::bitflags;
+#[cfg(feature = "crossterm")]
+use crossterm::{
+    cursor::{self, Hide, MoveTo, SetCursorStyle, Show},
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    queue,
+    style::{Colored, Print, SetAttribute, SetBackgroundColor, SetForegroundColor},
+    terminal::{
+        self, BeginSynchronizedUpdate, Clear, EndSynchronizedUpdate, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+};
+
+use super::{
+    style::{Attribute, Color},
+    terminal::CursorStyle,
+};
+
+/// Part of the screen to clear
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClearType {
+    All,
+    /// All cells and the scrollback history
+    Purge,
+    FromCursorDown,
+    FromCursorUp,
+    CurrentLine,
+    UntilNewLine,
+}
+
+bitflags! {
+    /// How keys are reported with the kitty keyboard protocol
+    #[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
+    pub struct KeyboardEnhancementFlags: u8 {
+        /// Tell a lone Escape from the start of an escape sequence
+        const DISAMBIGUATE_ESCAPE_CODES       = 0b0001;
+        /// Report key repeats and releases
+        const REPORT_EVENT_TYPES              = 0b0010;
+        /// Report the shifted and base layout keys
+        const REPORT_ALTERNATE_KEYS           = 0b0100;
+        /// Report every key, including text keys, as an escape sequence
+        const REPORT_ALL_KEYS_AS_ESCAPE_CODES = 0b1000;
+    }
+}
+
+/// Operations used by `Terminal` to render, output may be queued until `flush`
+pub trait Backend {
+    /// Move the cursor to a column and row
+    fn move_to(&mut self, x: u16, y: u16) -> io::Result<()>;
+
+    /// Print text at the cursor
+    fn print(&mut self, text: &str) -> io::Result<()>;
+
+    /// Change the foreground color
+    fn set_fg(&mut self, color: Color) -> io::Result<()>;
+
+    /// Change the background color
+    fn set_bg(&mut self, color: Color) -> io::Result<()>;
+
+    /// Change both colors at once
+    fn set_colors(&mut self, fg: Color, bg: Color) -> io::Result<()> {
+        self.set_fg(fg)?;
+        self.set_bg(bg)
+    }
+
+    /// Apply a text attribute
+    fn set_attribute(&mut self, attribute: Attribute) -> io::Result<()>;
+
+    /// Start a hyperlink to `url` or end the current one
+    fn set_link(&mut self, url: Option<&str>) -> io::Result<()> {
+        self.print(&format!("\x1b]8;;{}\x1b\\", url.unwrap_or("")))
+    }
+
+    /// Clear part of the screen with the current background
+    fn clear(&mut self, kind: ClearType) -> io::Result<()>;
+
+    fn show_cursor(&mut self) -> io::Result<()>;
+
+    fn hide_cursor(&mut self) -> io::Result<()>;
+
+    fn set_cursor_style(&mut self, style: CursorStyle) -> io::Result<()>;
+
+    fn enter_alternate_screen(&mut self) -> io::Result<()>;
+
+    fn leave_alternate_screen(&mut self) -> io::Result<()>;
+
+    fn enable_raw_mode(&mut self) -> io::Result<()>;
+
+    fn disable_raw_mode(&mut self) -> io::Result<()>;
+
+    fn enable_mouse(&mut self) -> io::Result<()>;
+
+    fn disable_mouse(&mut self) -> io::Result<()>;
+
+    /// Report pasted text as a single event, nothing by default
+    fn enable_bracketed_paste(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Report pasted text as typed keys, nothing by default
+    fn disable_bracketed_paste(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Whether the terminal implements the kitty keyboard protocol, false by default
+    fn supports_keyboard_enhancement(&mut self) -> io::Result<bool> {
+        Ok(false)
+    }
+
+    /// Push keyboard reporting flags on the terminal stack, nothing by default
+    fn push_keyboard_enhancement(&mut self, _flags: KeyboardEnhancementFlags) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Restore the keyboard reporting flags of the previous push, nothing by default
+    fn pop_keyboard_enhancement(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Start presenting a frame atomically, nothing by default
+    fn begin_synchronized_update(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// End presenting a frame atomically, nothing by default
+    fn end_synchronized_update(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Size of the terminal in columns and rows
+    fn size(&self) -> io::Result<(u16, u16)>;
+
+    /// Column and row of the cursor
+    fn cursor_position(&mut self) -> io::Result<(u16, u16)>;
+
+    /// Write out the queued output
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+/// Backend discarding its output, with a fixed size and the cursor at the origin. Useful to
+/// test drawing code without a terminal.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HeadlessBackend {
+    size: (u16, u16),
+}
+
+impl HeadlessBackend {
+    pub fn new(nb_col: u16, nb_row: u16) -> Self {
+        HeadlessBackend {
+            size: (nb_col, nb_row),
+        }
+    }
+}
+
+impl Backend for HeadlessBackend {
+    fn move_to(&mut self, _: u16, _: u16) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn print(&mut self, _: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_fg(&mut self, _: Color) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_bg(&mut self, _: Color) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_attribute(&mut self, _: Attribute) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_link(&mut self, _: Option<&str>) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn clear(&mut self, _: ClearType) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_cursor_style(&mut self, _: CursorStyle) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn enter_alternate_screen(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn leave_alternate_screen(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn enable_raw_mode(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn disable_raw_mode(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn enable_mouse(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn disable_mouse(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<(u16, u16)> {
+        Ok(self.size)
+    }
+
+    fn cursor_position(&mut self) -> io::Result<(u16, u16)> {
+        Ok((0, 0))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Backend writing crossterm escape sequences, the terminal mode and size being the ones of the
+/// process terminal
+#[cfg(feature = "crossterm")]
+pub struct CrosstermBackend<W: Write> {
+    /// Output is buffered until flushed
+    out: BufWriter<W>,
+}
+
+#[cfg(feature = "crossterm")]
+impl<W: Write> CrosstermBackend<W> {
+    pub fn new(out: W) -> Self {
+        CrosstermBackend {
+            out: BufWriter::new(out),
+        }
+    }
+
+    /// Underlying writer, output queued since the last flush is not written to it yet
+    pub fn writer(&mut self) -> &mut W {
+        self.out.get_mut()
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl<W: Write> Backend for CrosstermBackend<W> {
+    fn move_to(&mut self, x: u16, y: u16) -> io::Result<()> {
+        queue!(self.out, MoveTo(x, y))
+    }
+
+    fn print(&mut self, text: &str) -> io::Result<()> {
+        queue!(self.out, Print(text))
+    }
+
+    fn set_fg(&mut self, color: Color) -> io::Result<()> {
+        queue!(self.out, SetForegroundColor(color.into()))
+    }
+
+    fn set_bg(&mut self, color: Color) -> io::Result<()> {
+        queue!(self.out, SetBackgroundColor(color.into()))
+    }
+
+    fn set_colors(&mut self, fg: Color, bg: Color) -> io::Result<()> {
+        // A single sequence, crossterm SetColors emitting one per color
+        let fg = Colored::ForegroundColor(fg.into());
+        let bg = Colored::BackgroundColor(bg.into());
+        write!(self.out, "\x1b[{fg};{bg}m")
+    }
+
+    fn set_attribute(&mut self, attribute: Attribute) -> io::Result<()> {
+        queue!(self.out, SetAttribute(attribute.into()))
+    }
+
+    fn clear(&mut self, kind: ClearType) -> io::Result<()> {
+        queue!(self.out, Clear(kind.into()))
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        queue!(self.out, Show)
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        queue!(self.out, Hide)
+    }
+
+    fn set_cursor_style(&mut self, style: CursorStyle) -> io::Result<()> {
+        queue!(self.out, SetCursorStyle::from(style))
+    }
+
+    fn enter_alternate_screen(&mut self) -> io::Result<()> {
+        queue!(self.out, EnterAlternateScreen)
+    }
+
+    fn leave_alternate_screen(&mut self) -> io::Result<()> {
+        queue!(self.out, LeaveAlternateScreen)
+    }
+
+    fn enable_raw_mode(&mut self) -> io::Result<()> {
+        terminal::enable_raw_mode()
+    }
+
+    fn disable_raw_mode(&mut self) -> io::Result<()> {
+        terminal::disable_raw_mode()
+    }
+
+    fn enable_mouse(&mut self) -> io::Result<()> {
+        queue!(self.out, EnableMouseCapture)
+    }
+
+    fn disable_mouse(&mut self) -> io::Result<()> {
+        queue!(self.out, DisableMouseCapture)
+    }
+
+    fn enable_bracketed_paste(&mut self) -> io::Result<()> {
+        queue!(self.out, EnableBracketedPaste)
+    }
+
+    fn disable_bracketed_paste(&mut self) -> io::Result<()> {
+        queue!(self.out, DisableBracketedPaste)
+    }
+
+    fn supports_keyboard_enhancement(&mut self) -> io::Result<bool> {
+        // Pending output must reach the terminal before it answers the query
+        self.out.flush()?;
+        terminal::supports_keyboard_enhancement()
+    }
+
+    fn push_keyboard_enhancement(&mut self, flags: KeyboardEnhancementFlags) -> io::Result<()> {
+        queue!(self.out, PushKeyboardEnhancementFlags(flags.into()))
+    }
+
+    fn pop_keyboard_enhancement(&mut self) -> io::Result<()> {
+        queue!(self.out, PopKeyboardEnhancementFlags)
+    }
+
+    fn begin_synchronized_update(&mut self) -> io::Result<()> {
+        queue!(self.out, BeginSynchronizedUpdate)
+    }
+
+    fn end_synchronized_update(&mut self) -> io::Result<()> {
+        queue!(self.out, EndSynchronizedUpdate)
+    }
+
+    fn size(&self) -> io::Result<(u16, u16)> {
+        terminal::size()
+    }
+
+    fn cursor_position(&mut self) -> io::Result<(u16, u16)> {
+        cursor::position()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl From<Color> for crossterm::style::Color {
+    fn from(color: Color) -> Self {
+        use crossterm::style::Color as C;
+        match color {
+            Color::Reset => C::Reset,
+            Color::Black => C::Black,
+            Color::DarkGrey => C::DarkGrey,
+            Color::Red => C::Red,
+            Color::DarkRed => C::DarkRed,
+            Color::Green => C::Green,
+            Color::DarkGreen => C::DarkGreen,
+            Color::Yellow => C::Yellow,
+            Color::DarkYellow => C::DarkYellow,
+            Color::Blue => C::Blue,
+            Color::DarkBlue => C::DarkBlue,
+            Color::Magenta => C::Magenta,
+            Color::DarkMagenta => C::DarkMagenta,
+            Color::Cyan => C::Cyan,
+            Color::DarkCyan => C::DarkCyan,
+            Color::White => C::White,
+            Color::Grey => C::Grey,
+            Color::Rgb { r, g, b } => C::Rgb { r, g, b },
+            Color::AnsiValue(v) => C::AnsiValue(v),
+        }
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl From<Attribute> for crossterm::style::Attribute {
+    fn from(attribute: Attribute) -> Self {
+        use crossterm::style::Attribute as A;
+        match attribute {
+            Attribute::Reset => A::Reset,
+            Attribute::Bold => A::Bold,
+            Attribute::Dim => A::Dim,
+            Attribute::Italic => A::Italic,
+            Attribute::Underlined => A::Underlined,
+            Attribute::SlowBlink => A::SlowBlink,
+            Attribute::RapidBlink => A::RapidBlink,
+            Attribute::Reverse => A::Reverse,
+            Attribute::Hidden => A::Hidden,
+            Attribute::CrossedOut => A::CrossedOut,
+            Attribute::NormalIntensity => A::NormalIntensity,
+            Attribute::NoItalic => A::NoItalic,
+            Attribute::NoUnderline => A::NoUnderline,
+            Attribute::NoBlink => A::NoBlink,
+            Attribute::NoReverse => A::NoReverse,
+            Attribute::NoHidden => A::NoHidden,
+            Attribute::NotCrossedOut => A::NotCrossedOut,
+        }
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl From<ClearType> for crossterm::terminal::ClearType {
+    fn from(kind: ClearType) -> Self {
+        use crossterm::terminal::ClearType as C;
+        match kind {
+            ClearType::All => C::All,
+            ClearType::Purge => C::Purge,
+            ClearType::FromCursorDown => C::FromCursorDown,
+            ClearType::FromCursorUp => C::FromCursorUp,
+            ClearType::CurrentLine => C::CurrentLine,
+            ClearType::UntilNewLine => C::UntilNewLine,
+        }
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl From<KeyboardEnhancementFlags> for crossterm::event::KeyboardEnhancementFlags {
+    fn from(flags: KeyboardEnhancementFlags) -> Self {
+        Self::from_bits_truncate(flags.bits())
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl From<CursorStyle> for SetCursorStyle {
+    fn from(style: CursorStyle) -> Self {
+        match style {
+            CursorStyle::Default => SetCursorStyle::DefaultUserShape,
+            CursorStyle::BlinkingBlock => SetCursorStyle::BlinkingBlock,
+            CursorStyle::SteadyBlock => SetCursorStyle::SteadyBlock,
+            CursorStyle::BlinkingUnderline => SetCursorStyle::BlinkingUnderScore,
+            CursorStyle::SteadyUnderline => SetCursorStyle::SteadyUnderScore,
+            CursorStyle::BlinkingBar => SetCursorStyle::BlinkingBar,
+            CursorStyle::SteadyBar => SetCursorStyle::SteadyBar,
+        }
+    }
+}
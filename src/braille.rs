@@ -0,0 +1,93 @@
+use super::{style::Style, Canvas};
+
+/// Bit of each dot of a braille pattern, indexed by [y][x]
+const DOTS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// Dots grid drawn with braille patterns, each cell holding 2x4 dots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Braille {
+    nb_col: usize,
+    nb_row: usize,
+    cells: Vec<u8>,
+}
+
+impl Braille {
+    /// Grid of `width` by `height` dots
+    pub fn new(width: usize, height: usize) -> Self {
+        let (nb_col, nb_row) = (width.div_ceil(2), height.div_ceil(4));
+        Braille {
+            nb_col,
+            nb_row,
+            cells: vec![0; nb_col * nb_row],
+        }
+    }
+
+    /// Light the dot at (x, y), ignored if outside the grid
+    pub fn set(&mut self, x: usize, y: usize) {
+        let (col, row) = (x / 2, y / 4);
+        if col < self.nb_col && row < self.nb_row {
+            self.cells[row * self.nb_col + col] |= DOTS[y % 4][x % 2];
+        }
+    }
+
+    /// Glyph of the cell at (col, row)
+    fn glyph(&self, col: usize, row: usize) -> char {
+        char::from_u32(0x2800 + self.cells[row * self.nb_col + col] as u32).unwrap()
+    }
+
+    /// Write the cells at the top of the canvas, leaving the cells without dots untouched
+    pub fn render(&self, c: &mut Canvas, style: Style) {
+        for row in 0..self.nb_row {
+            let mut line = c.top();
+            for col in 0..self.nb_col {
+                if self.cells[row * self.nb_col + col] == 0 {
+                    line.skip(1);
+                } else {
+                    line.draw_char(self.glyph(col, row), style);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{buffer::Buffer, style::none};
+
+    use super::*;
+
+    #[test]
+    fn dots() {
+        let mut braille = Braille::new(2, 4);
+        braille.set(0, 0);
+        assert_eq!(braille.glyph(0, 0), '⠁');
+
+        let mut braille = Braille::new(2, 4);
+        braille.set(0, 3);
+        assert_eq!(braille.glyph(0, 0), '⡀');
+
+        let mut braille = Braille::new(2, 4);
+        braille.set(1, 3);
+        assert_eq!(braille.glyph(0, 0), '⢀');
+
+        let mut braille = Braille::new(2, 4);
+        for x in 0..2 {
+            for y in 0..4 {
+                braille.set(x, y);
+            }
+        }
+        assert_eq!(braille.glyph(0, 0), '⣿');
+        // Outside
+        braille.set(2, 0);
+    }
+
+    #[test]
+    fn render() {
+        let mut braille = Braille::new(5, 5);
+        braille.set(0, 0);
+        braille.set(4, 4);
+        let mut buffer = Buffer::empty(3, 2);
+        braille.render(&mut buffer.canvas(), none());
+        assert_eq!(buffer.snapshot(), "⠁\n  ⠁");
+    }
+}
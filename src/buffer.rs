@@ -1,8 +1,8 @@
-use crossterm::style::Color;
-use unicode_width::UnicodeWidthChar;
+use std::sync::Arc;
 
 use super::{
-    style::{Modifier, Style},
+    str_width,
+    style::{none, Color, Modifier, Style},
     Area, Canvas,
 };
 
@@ -12,6 +12,8 @@ pub(crate) struct Buffer {
     pub nb_col: usize,
     pub content: Vec<Cell>,
     pub cursor_pos: Option<(usize, usize)>,
+    /// Content of cleared cells
+    pub blank: Cell,
 }
 
 impl Buffer {
@@ -33,6 +35,7 @@ impl Buffer {
             nb_row,
             content,
             cursor_pos: None,
+            blank: Cell::default(),
         }
     }
 
@@ -45,6 +48,8 @@ impl Buffer {
                 h: self.nb_row,
             },
             buf: self,
+            base: none(),
+            styles: Vec::new(),
         }
     }
 
@@ -71,25 +76,157 @@ impl Buffer {
     }
 
     pub fn char_at(&mut self, index: usize, c: char, style: Style) {
-        self.content[index].set_char(c).set_style(style);
+        let cell = &mut self.content[index];
+        cell.set_char(c).set_style(style);
+        cell.link = None;
+    }
+
+    pub fn symbol_at(&mut self, index: usize, symbol: &str, style: Style) {
+        let cell = &mut self.content[index];
+        cell.set_symbol(symbol).set_style(style);
+        cell.link = None;
+    }
+
+    /// Swap two rows of an area
+    fn swap_rows(&mut self, area: &Area, a: usize, b: usize) {
+        for x in area.x..area.x + area.w {
+            let (a, b) = (self.index_of(x, a), self.index_of(x, b));
+            self.content.swap(a, b);
+        }
+    }
+
+    /// Clear a row of an area
+    fn reset_row(&mut self, area: &Area, y: usize) {
+        for x in area.x..area.x + area.w {
+            let i = self.index_of(x, y);
+            self.content[i].clone_from(&self.blank);
+        }
+    }
+
+    /// Shift the rows of an area up by `n`, blanking the exposed rows at the bottom
+    pub fn scroll_up(&mut self, area: &Area, n: usize) {
+        let n = n.min(area.h);
+        let end = area.y + area.h;
+        for y in area.y..end - n {
+            self.swap_rows(area, y, y + n);
+        }
+        for y in end - n..end {
+            self.reset_row(area, y);
+        }
+    }
+
+    /// Shift the rows of an area down by `n`, blanking the exposed rows at the top
+    pub fn scroll_down(&mut self, area: &Area, n: usize) {
+        let n = n.min(area.h);
+        for y in (area.y + n..area.y + area.h).rev() {
+            self.swap_rows(area, y, y - n);
+        }
+        for y in area.y..area.y + n {
+            self.reset_row(area, y);
+        }
+    }
+
+    /// Copy the `src` area at `(x, y)`, clipping at the buffer bounds. Overlapping areas are
+    /// copied in an order reading each source cell before it is overwritten.
+    pub fn copy_region(&mut self, src: &Area, x: usize, y: usize) {
+        let w = src.w.min(self.nb_col.saturating_sub(src.x.max(x)));
+        let h = src.h.min(self.nb_row.saturating_sub(src.y.max(y)));
+        // Walk backward along the axes where the destination is after the source
+        for j in 0..h {
+            let dy = if y > src.y { h - 1 - j } else { j };
+            for i in 0..w {
+                let dx = if x > src.x { w - 1 - i } else { i };
+                let cell = self.content[self.index_of(src.x + dx, src.y + dy)].clone();
+                let i = self.index_of(x + dx, y + dy);
+                self.content[i] = cell;
+            }
+        }
+    }
+
+    /// Copy the `src` area of another buffer at `(x, y)` of the `dst` area, clipping at its
+    /// bounds. Blank cells are skipped so that the background shows through.
+    pub fn overlay(&mut self, other: &Buffer, src: &Area, dst: &Area, x: usize, y: usize) {
+        let blank = &other.blank;
+        let (end_x, end_y) = (dst.x + dst.w, dst.y + dst.h);
+        for sy in 0..src.h {
+            let dy = dst.y + y + sy;
+            if dy >= end_y {
+                break;
+            }
+            let mut skip = false;
+            for sx in 0..src.w {
+                let dx = dst.x + x + sx;
+                if dx >= end_x {
+                    break;
+                }
+                let cell = &other.content[other.index_of(src.x + sx, src.y + sy)];
+                if std::mem::take(&mut skip) || cell == blank {
+                    continue;
+                }
+                let i = self.index_of(dx, dy);
+                let width = cell.width();
+                if dx + width > end_x {
+                    // Do not half draw a wide char
+                    self.char_at(i, ' ', cell.style());
+                } else {
+                    self.content[i].clone_from(cell);
+                    skip = width > 1;
+                }
+            }
+        }
     }
 
     /// Resize the buffer so that the mapped area matches the given area and that the buffer
-    /// length is equal to area.width * area.height
+    /// length is equal to area.width * area.height. Cells keep their coordinates, the ones
+    /// falling outside the new bounds are dropped.
     pub fn resize(&mut self, nb_col: usize, nb_row: usize) {
-        self.content.resize(nb_col * nb_row, Default::default());
+        let mut content = Vec::with_capacity(nb_col * nb_row);
+        for y in 0..nb_row {
+            for x in 0..nb_col {
+                if x < self.nb_col && y < self.nb_row {
+                    let i = self.index_of(x, y);
+                    content.push(std::mem::take(&mut self.content[i]));
+                } else {
+                    content.push(self.blank.clone());
+                }
+            }
+        }
+        self.content = content;
         self.nb_col = nb_col;
         self.nb_row = nb_row;
     }
 
+    /// Clear cells to the default cell with a base style
+    pub fn set_base_style(&mut self, style: Style) {
+        self.blank.reset();
+        self.blank.set_style(style);
+    }
+
     /// Reset all cells in the buffer
     pub fn reset(&mut self) {
         self.cursor_pos.take();
         for c in &mut self.content {
-            c.reset();
+            c.clone_from(&self.blank);
         }
     }
 
+    /// Render the buffer as plain text, one line per row without trailing blanks
+    pub fn snapshot(&self) -> String {
+        let mut lines = Vec::with_capacity(self.nb_row);
+        for row in self.content.chunks(self.nb_col.max(1)) {
+            let mut line = String::with_capacity(row.len());
+            let mut skip = false;
+            for cell in row {
+                if !skip {
+                    line.push_str(&cell.symbol);
+                }
+                skip = cell.width() > 1;
+            }
+            lines.push(line.trim_end_matches(' ').to_string());
+        }
+        lines.join("\n")
+    }
+
     /// Builds a minimal sequence of coordinates and Cells necessary to update the UI from
     /// self to other.
     pub fn diff<'a>(&self, other: &'a Buffer) -> Vec<(u16, u16, &'a Cell)> {
@@ -106,26 +243,83 @@ impl Buffer {
                 updates.push((x, y, &next_buffer[i]));
             }
 
-            skip = current.char.width().unwrap_or(0) > 1;
+            skip = current.width() > 1;
         }
         updates
     }
+
+    /// Like `diff` but clearing to the end of the row when its trailing cells become blank
+    pub fn diff_with_clear<'a>(&self, other: &'a Buffer) -> Vec<Change<'a>> {
+        let blank = &other.blank;
+        let width = self.nb_col.max(1);
+        // First changed cell of the blank tail of each row
+        let clears: Vec<_> = (self.content.chunks(width))
+            .zip(other.content.chunks(width))
+            .map(|(prev, next)| {
+                let tail = next
+                    .iter()
+                    .rposition(|c| c != blank)
+                    .map_or(0, |i| (i + next[i].width().max(1)).min(next.len()));
+                (tail..next.len()).find(|&x| prev[x] != next[x])
+            })
+            .collect();
+        let mut changes = Vec::new();
+        for (x, y, cell) in self.diff(other) {
+            match clears[y as usize] {
+                Some(start) if x as usize == start => changes.push(Change::ClearLine(x, y, blank)),
+                Some(start) if x as usize > start => {}
+                _ => changes.push(Change::Cell(x, y, cell)),
+            }
+        }
+        changes
+    }
+}
+
+/// Update to apply on screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Change<'a> {
+    /// Draw a cell
+    Cell(u16, u16, &'a Cell),
+    /// Clear from a position to the end of its row, filling with the style of a blank cell
+    ClearLine(u16, u16, &'a Cell),
+}
+
+impl Change<'_> {
+    pub fn pos(&self) -> (u16, u16) {
+        match *self {
+            Change::Cell(x, y, _) | Change::ClearLine(x, y, _) => (x, y),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) struct Cell {
-    pub char: char,
+    /// Grapheme cluster displayed in the cell
+    pub symbol: String,
     pub fg: Color,
     pub bg: Color,
     pub modifier: Modifier,
+    pub link: Option<Arc<str>>,
 }
 
 impl Cell {
     pub fn set_char(&mut self, ch: char) -> &mut Cell {
-        self.char = ch;
+        self.symbol.clear();
+        self.symbol.push(ch);
         self
     }
 
+    pub fn set_symbol(&mut self, symbol: &str) -> &mut Cell {
+        self.symbol.clear();
+        self.symbol.push_str(symbol);
+        self
+    }
+
+    /// Printed width of the symbol
+    pub fn width(&self) -> usize {
+        str_width(&self.symbol)
+    }
+
     pub fn set_style(&mut self, style: Style) -> &mut Cell {
         if let Some(c) = style.fg {
             self.fg = c;
@@ -137,21 +331,31 @@ impl Cell {
         self
     }
 
+    pub fn style(&self) -> Style {
+        Style {
+            fg: Some(self.fg),
+            bg: Some(self.bg),
+            modifier: self.modifier,
+        }
+    }
+
     pub fn reset(&mut self) {
-        self.char = ' ';
+        self.set_char(' ');
         self.fg = Color::Reset;
         self.bg = Color::Reset;
         self.modifier = Modifier::empty();
+        self.link = None;
     }
 }
 
 impl Default for Cell {
     fn default() -> Cell {
         Cell {
-            char: ' ',
+            symbol: String::from(" "),
             fg: Color::Reset,
             bg: Color::Reset,
             modifier: Modifier::empty(),
+            link: None,
         }
     }
 }
@@ -160,7 +364,10 @@ impl Default for Cell {
 mod tests {
     use unicode_width::UnicodeWidthStr;
 
-    use crate::style::none;
+    use crate::{
+        frame::Frame,
+        style::{none, rgb},
+    };
 
     use super::*;
 
@@ -259,6 +466,775 @@ mod tests {
         // Only 1 space left.
         buffer.canvas().line("コンピ", none());
         assert_eq!(buffer, buf_lines(&["コン "]));
+
+        // The space left is styled
+        let mut buffer = Buffer::filled(5, 1, &cell('x'));
+        let style = none().bg(Color::Blue);
+        buffer.canvas().line("コンピ", style);
+        assert_eq!(buffer.content[4], *cell(' ').set_style(style));
+    }
+
+    #[test]
+    fn line_cdraw_double_width() {
+        let mut buffer = Buffer::empty(9, 1);
+        buffer.canvas().top().cdraw("コンピ", none());
+        assert_eq!(buffer, buf_lines(&[" コンピ  "]));
+
+        // Truncate like draw when too wide
+        let mut buffer = Buffer::empty(5, 1);
+        buffer.canvas().top().cdraw("コンピ", none());
+        assert_eq!(buffer, buf_lines(&["コン "]));
+    }
+
+    #[test]
+    fn line_rdraw_keep_tail() {
+        let mut buffer = Buffer::empty(5, 1);
+        buffer.canvas().top().rdraw("hello.rs", none());
+        assert_eq!(buffer, buf_lines(&["lo.rs"]));
+
+        let mut buffer = Buffer::empty(6, 1);
+        buffer.canvas().top().rdraw("コンピュータ", none());
+        assert_eq!(buffer, buf_lines(&["ュータ"]));
+
+        // Never split a wide char
+        let mut buffer = Buffer::empty(5, 1);
+        buffer.canvas().top().rdraw("コンピュータ", none());
+        assert_eq!(buffer, buf_lines(&[" ータ"]));
+    }
+
+    #[test]
+    fn line_draw_truncated() {
+        // Exact fit
+        let mut buffer = Buffer::empty(5, 1);
+        buffer.canvas().top().draw_truncated("hello", none(), "…");
+        assert_eq!(buffer, buf_lines(&["hello"]));
+
+        // One column over
+        let mut buffer = Buffer::empty(5, 1);
+        buffer.canvas().top().draw_truncated("hello!", none(), "…");
+        assert_eq!(buffer, buf_lines(&["hell…"]));
+        let mut buffer = Buffer::empty(5, 1);
+        buffer
+            .canvas()
+            .top()
+            .draw_truncated("hello!", none(), "...");
+        assert_eq!(buffer, buf_lines(&["he..."]));
+
+        // Double width
+        let mut buffer = Buffer::empty(6, 1);
+        buffer
+            .canvas()
+            .top()
+            .draw_truncated("コンピュータ", none(), "…");
+        assert_eq!(buffer, buf_lines(&["コン… "]));
+    }
+
+    #[test]
+    fn line_fill() {
+        let mut buffer = Buffer::empty(5, 1);
+        let mut c = buffer.canvas();
+        let mut line = c.top();
+        line.draw("ab", none()).fill(none().bg(Color::Blue));
+        assert_eq!(line.width(), 0);
+        assert_eq!(buffer.content[1].bg, Color::Reset);
+        assert_eq!(buffer.content[2].bg, Color::Blue);
+        assert_eq!(buffer.content[4].bg, Color::Blue);
+
+        let mut buffer = Buffer::empty(5, 1);
+        buffer.canvas().top().fill_char('─', none());
+        assert_eq!(buffer, buf_lines(&["─────"]));
+    }
+
+    #[test]
+    fn canvas_border() {
+        let mut buffer = Buffer::empty(4, 4);
+        buffer.canvas().border(none()).line("ab", none());
+        assert_eq!(buffer, buf_lines(&["┌──┐", "│ab│", "│  │", "└──┘"]));
+
+        // Too small
+        let mut buffer = Buffer::empty(4, 1);
+        buffer.canvas().border(none());
+        assert_eq!(buffer, buf_lines(&["    "]));
+    }
+
+    #[test]
+    fn canvas_border_titled() {
+        let mut buffer = Buffer::empty(10, 5);
+        buffer
+            .canvas()
+            .consume(Area::new(0, 1, 9, 4))
+            .border_titled("Title", none());
+        let expected = buf_lines(&[
+            "          ",
+            "┌─Title─┐ ",
+            "│       │ ",
+            "│       │ ",
+            "└───────┘ ",
+        ]);
+        assert_eq!(buffer, expected);
+
+        // Title wider than the interior
+        let mut buffer = Buffer::empty(8, 2);
+        buffer.canvas().border_titled("Title", none());
+        assert_eq!(buffer, buf_lines(&["┌─Titl─┐", "└──────┘"]));
+    }
+
+    #[test]
+    fn canvas_vline_text() {
+        let mut buffer = Buffer::empty(1, 5);
+        buffer.canvas().vline_text("ABC", none());
+        assert_eq!(buffer, buf_lines(&["A", "B", "C", " ", " "]));
+
+        // Stop at the area height
+        let mut buffer = Buffer::empty(1, 2);
+        buffer.canvas().vline_text("ABC", none());
+        assert_eq!(buffer, buf_lines(&["A", "B"]));
+    }
+
+    #[test]
+    fn canvas_cell_at() {
+        let mut buffer = Buffer::empty(5, 2);
+        let mut c = buffer.canvas();
+        c.line("", none())
+            .line("abcTe", none().fg(Color::Red).bold());
+        let (symbol, style) = c.cell_at(3, 1).unwrap();
+        assert_eq!(symbol, "T");
+        assert_eq!(style.fg, Some(Color::Red));
+        assert_eq!(style.bg, Some(Color::Reset));
+        assert_eq!(style.modifier, Modifier::BOLD);
+        assert_eq!(c.cell_at(5, 1), None);
+        assert_eq!(c.cell_at(0, 2), None);
+    }
+
+    #[test]
+    fn buffer_snapshot() {
+        let buffer = buf_lines(&["┌称号─┐  ", "│ a  │  ", "└────┘  "]);
+        assert_eq!(buffer.snapshot(), "┌称号─┐\n│ a  │\n└────┘");
+    }
+
+    #[test]
+    fn buffer_set_string_grapheme_cluster() {
+        let mut buffer = Buffer::empty(3, 1);
+        buffer.canvas().line("e\u{301}xコ", none());
+        assert_eq!(buffer.content[0].symbol, "e\u{301}");
+        assert_eq!(buffer.content[1].symbol, "x");
+        assert_eq!(buffer.content[2].symbol, " ");
+        assert_eq!(buffer.snapshot(), "e\u{301}x");
+    }
+
+    #[test]
+    fn line_expand_tabs() {
+        let mut buffer = Buffer::empty(8, 1);
+        buffer.canvas().top().tab_width(4).draw("a\tb", none());
+        assert_eq!(buffer, buf_lines(&["a   b   "]));
+
+        // Relative to the line start and stopping at the line end
+        let mut buffer = Buffer::empty(8, 1);
+        let mut c = buffer.canvas();
+        c.reserve_left(2);
+        c.top().tab_width(4).draw("abcd\tef", none());
+        assert_eq!(buffer, buf_lines(&["  abcd  "]));
+
+        // Default tab width
+        let mut buffer = Buffer::empty(10, 1);
+        buffer.canvas().line("\tb", none());
+        assert_eq!(buffer, buf_lines(&["        b "]));
+    }
+
+    #[test]
+    fn line_fit_exact() {
+        let mut buffer = Buffer::empty(5, 1);
+        let mut c = buffer.canvas();
+        let line = c.top();
+        assert!(line.fit("12345"));
+        assert!(!line.fit("123456"));
+    }
+
+    #[test]
+    fn canvas_wrap_exact() {
+        let mut buffer = Buffer::empty(5, 3);
+        buffer.canvas().wrap("12345 12345", none());
+        assert_eq!(buffer, buf_lines(&["12345", "12345", "     "]));
+
+        let mut buffer = Buffer::empty(5, 3);
+        buffer.canvas().wrap("ab cd ef gh", none());
+        assert_eq!(buffer, buf_lines(&["ab cd", "ef gh", "     "]));
+
+        // Trailing whitespace on a full line before a newline
+        let mut buffer = Buffer::empty(3, 3);
+        assert_eq!(buffer.canvas().wrap("abc \nd", none()), 2);
+        assert_eq!(buffer.snapshot(), "abc\nd\n");
+    }
+
+    #[test]
+    fn line_draw_char() {
+        let mut buffer = Buffer::empty(4, 1);
+        let mut c = buffer.canvas();
+        let mut line = c.top();
+        line.draw_char('█', none());
+        assert_eq!(line.width(), 3);
+        line.draw_char('コ', none()).draw_char('\u{301}', none());
+        assert_eq!(line.width(), 1);
+        line.draw_char('ン', none());
+        assert_eq!(line.width(), 1);
+        assert_eq!(buffer, buf_lines(&["█コ "]));
+    }
+
+    #[test]
+    fn line_draw_highlighted() {
+        let hl = none().bg(Color::Yellow);
+        let highlighted = |buffer: &Buffer| -> String {
+            (buffer.content.iter())
+                .map(|c| if c.bg == Color::Yellow { '^' } else { ' ' })
+                .collect()
+        };
+
+        let mut buffer = Buffer::empty(11, 1);
+        (buffer.canvas().top()).draw_highlighted("hello world", "lo", none(), hl);
+        assert_eq!(buffer.snapshot(), "hello world");
+        assert_eq!(highlighted(&buffer), "   ^^      ");
+
+        // Overlapping, case insensitive and multibyte
+        let mut buffer = Buffer::empty(9, 1);
+        (buffer.canvas().top()).draw_highlighted("AaAコbe\u{301}", "aa", none(), hl);
+        assert_eq!(highlighted(&buffer), "^^^      ");
+        let mut buffer = Buffer::empty(9, 1);
+        (buffer.canvas().top()).draw_highlighted("AaAコbe\u{301}", "コbe", none(), hl);
+        assert_eq!(highlighted(&buffer), "   ^ ^^  ");
+        assert_eq!(buffer.snapshot(), "AaAコbe\u{301}");
+
+        // Truncated
+        let mut buffer = Buffer::empty(4, 1);
+        (buffer.canvas().top()).draw_highlighted("hello world", "lo", none(), hl);
+        assert_eq!(buffer.snapshot(), "hell");
+        assert_eq!(highlighted(&buffer), "   ^");
+
+        // A wide glyph at the edge ends the line
+        let mut buffer = Buffer::empty(3, 1);
+        (buffer.canvas().top()).draw_highlighted("ab界x", "x", none(), hl);
+        assert_eq!(buffer.snapshot(), "ab");
+    }
+
+    #[test]
+    fn line_draw_visible() {
+        let mut buffer = Buffer::empty(12, 1);
+        buffer
+            .canvas()
+            .top()
+            .draw_visible("a\x01b\x7f\u{85}\t", none());
+        assert_eq!(buffer.snapshot(), "a^Ab^?\\u{85}");
+        assert_eq!(
+            buffer.content[1].style(),
+            cell(' ').set_style(none().dim()).style()
+        );
+        assert_eq!(buffer.content[3].style(), cell(' ').style());
+
+        // Zero-width and bidi format chars
+        let mut buffer = Buffer::empty(20, 1);
+        (buffer.canvas().top()).draw_visible("a\u{200B}b\u{202E}c", none());
+        assert_eq!(buffer.snapshot(), "a\\u{200b}b\\u{202e}c");
+        // Joiners in emoji sequences are left alone
+        let mut buffer = Buffer::empty(4, 1);
+        (buffer.canvas().top()).draw_visible("👩\u{200D}💻", none());
+        assert_eq!(buffer.snapshot(), "👩\u{200D}💻");
+    }
+
+    #[test]
+    fn line_skip() {
+        let mut buffer = buf_lines(&["abcdef"]);
+        buffer
+            .canvas()
+            .top()
+            .draw("1", none())
+            .skip(3)
+            .draw("2", none());
+        assert_eq!(buffer, buf_lines(&["1bcd2f"]));
+
+        let mut buffer = Buffer::empty(3, 1);
+        let mut c = buffer.canvas();
+        let mut line = c.top();
+        line.skip(5);
+        assert_eq!(line.width(), 0);
+    }
+
+    #[test]
+    fn line_three() {
+        let mut buffer = Buffer::empty(11, 1);
+        buffer.canvas().top().three("ab", "mid", "cd", none());
+        assert_eq!(buffer, buf_lines(&["ab  mid  cd"]));
+
+        // Center truncated between the sides
+        let mut buffer = Buffer::empty(7, 1);
+        buffer.canvas().top().three("ab", "middle", "cd", none());
+        assert_eq!(buffer, buf_lines(&["abmidcd"]));
+
+        // Sides overflowing
+        let mut buffer = Buffer::empty(5, 1);
+        buffer.canvas().top().three("abc", "mid", "def", none());
+        assert_eq!(buffer, buf_lines(&["abcef"]));
+    }
+
+    #[test]
+    fn line_used() {
+        let mut buffer = Buffer::empty(5, 2);
+        let mut c = buffer.canvas();
+        c.top();
+        let mut line = c.top();
+        line.draw("コンピ", none());
+        assert_eq!(line.used(), 4);
+        assert_eq!(line.width(), 1);
+        line.draw("\u{1}a", none());
+        assert_eq!(line.used(), 5);
+    }
+
+    #[test]
+    fn canvas_wrap_long_word() {
+        let mut buffer = Buffer::empty(8, 3);
+        buffer.canvas().wrap("abcdefghijklmnopqrst", none());
+        assert_eq!(buffer, buf_lines(&["abcdefgh", "ijklmnop", "qrst    "]));
+
+        let mut buffer = Buffer::empty(8, 3);
+        buffer.canvas().wrap("see abcdefghijk ok", none());
+        assert_eq!(buffer, buf_lines(&["see     ", "abcdefgh", "ijk ok  "]));
+    }
+
+    #[test]
+    fn canvas_wrap_newlines() {
+        let mut buffer = Buffer::empty(3, 4);
+        buffer.canvas().wrap("a\n\nb", none());
+        assert_eq!(buffer, buf_lines(&["a  ", "   ", "b  ", "   "]));
+
+        // Keep indentation after a newline
+        let mut buffer = Buffer::empty(6, 3);
+        buffer.canvas().wrap("ab cd\r\n  ef", none());
+        assert_eq!(buffer, buf_lines(&["ab cd ", "  ef  ", "      "]));
+    }
+
+    #[test]
+    fn canvas_wrap_indented() {
+        let mut buffer = Buffer::empty(8, 6);
+        let lines = buffer
+            .canvas()
+            .wrap_indented("- one two three four\n- five", none(), 2);
+        assert_eq!(lines, 5);
+        assert_eq!(buffer.snapshot(), "- one\n  two\n  three\n  four\n- five\n");
+    }
+
+    #[test]
+    fn canvas_wrap_justified() {
+        let mut buffer = Buffer::empty(11, 4);
+        let lines = buffer
+            .canvas()
+            .wrap_justified("a b c dddd eeeee f\nab cd ef gh ij", none());
+        assert_eq!(lines, 4);
+        assert_eq!(buffer.snapshot(), "a  b c dddd\neeeee f\nab cd ef gh\nij");
+
+        // Single word lines are left alone
+        let mut buffer = Buffer::empty(5, 2);
+        buffer.canvas().wrap_justified("abc defgh", none());
+        assert_eq!(buffer.snapshot(), "abc\ndefgh");
+    }
+
+    #[test]
+    fn canvas_wrap_lines() {
+        let mut buffer = Buffer::empty(5, 5);
+        let mut c = buffer.canvas();
+        assert_eq!(c.wrap("ab cd ef", none()), 2);
+        assert_eq!(c.height(), 3);
+        assert_eq!(c.wrap("", none()), 0);
+        assert_eq!(c.wrap("a\n\n", none()), 2);
+        assert_eq!(c.wrap("ab cd ef", none()), 1);
+    }
+
+    #[test]
+    fn line_draw_spans() {
+        let (red, blue) = (none().fg(Color::Red), none().fg(Color::Blue));
+        let mut buffer = Buffer::empty(4, 1);
+        buffer
+            .canvas()
+            .top()
+            .draw_spans(&[("foo", red), ("bar", blue), ("baz", red)]);
+        assert_eq!(buffer.snapshot(), "foob");
+        let colors: Vec<_> = buffer.content.iter().map(|c| c.fg).collect();
+        assert_eq!(colors, [Color::Red, Color::Red, Color::Red, Color::Blue]);
+
+        // A wide glyph at the edge ends the line
+        let mut buffer = Buffer::empty(3, 1);
+        (buffer.canvas().top()).draw_spans(&[("ab", red), ("界", blue), ("x", red)]);
+        assert_eq!(buffer.snapshot(), "ab");
+    }
+
+    #[test]
+    fn line_gradient() {
+        let mut buffer = Buffer::empty(4, 1);
+        buffer
+            .canvas()
+            .top()
+            .gradient("ab\u{200b}cd", Color::Red, rgb(0, 0, 255));
+        assert_eq!(buffer.snapshot(), "abcd");
+        let colors: Vec<_> = buffer.content.iter().map(|c| c.fg).collect();
+        assert_eq!(
+            colors,
+            [
+                rgb(255, 0, 0),
+                rgb(170, 0, 85),
+                rgb(85, 0, 170),
+                rgb(0, 0, 255)
+            ]
+        );
+
+        // A wide glyph at the edge ends the line, its cell being blanked
+        let mut buffer = buf_lines(&["...."]);
+        (buffer.canvas().top()).gradient("ab界x", Color::Red, rgb(0, 0, 255));
+        assert_eq!(buffer.snapshot(), "ab界");
+        let mut buffer = buf_lines(&["..."]);
+        (buffer.canvas().top()).gradient("ab界x", Color::Red, rgb(0, 0, 255));
+        assert_eq!(buffer.snapshot(), "ab");
+        assert_eq!(buffer.content[2].fg, rgb(0, 0, 255));
+
+        // Ending on a wide glyph reaches the last color
+        let mut buffer = Buffer::empty(5, 1);
+        (buffer.canvas().top()).gradient("a界界", Color::Red, rgb(0, 0, 255));
+        let colors = [0, 1, 3].map(|i| buffer.content[i].fg);
+        assert_eq!(colors, [rgb(255, 0, 0), rgb(128, 0, 128), rgb(0, 0, 255)]);
+    }
+
+    #[test]
+    fn line_sparkline() {
+        let mut buffer = Buffer::empty(10, 4);
+        let mut c = buffer.canvas();
+        c.top().sparkline(&[0, 1, 2, 3, 4, 5, 6, 7], none());
+        c.top().sparkline(&[5, 5, 5], none());
+        // Grouped by pairs
+        c.top().sparkline(&(0..20).collect::<Vec<_>>(), none());
+        c.top().sparkline(&[], none());
+        assert_eq!(buffer.snapshot(), "▁▂▃▄▅▆▇█\n▄▄▄\n▁▂▂▃▄▅▅▆▇█\n");
+    }
+
+    #[test]
+    fn line_gauge() {
+        let mut buffer = Buffer::empty(4, 4);
+        let mut c = buffer.canvas();
+        c.top().gauge(0.5, none());
+        c.top().gauge(0.55, none());
+        c.top().gauge(1.5, none());
+        c.top().gauge(-1.0, none());
+        assert_eq!(buffer, buf_lines(&["██  ", "██▎ ", "████", "    "]));
+    }
+
+    #[test]
+    fn canvas_style_stack() {
+        let mut buffer = Buffer::empty(3, 3);
+        let mut c = buffer.canvas();
+        c.push_style(none().fg(Color::Red));
+        c.push_style(none().bold());
+        let area = c.reserve_top(1);
+        c.sub(area).top().draw_default("a");
+        c.pop_style();
+        c.top().draw_default("b");
+        c.pop_style().pop_style();
+        c.top().draw_default("c").draw("d", none().italic());
+        let styles: Vec<_> = buffer.content.iter().step_by(3).map(Cell::style).collect();
+        assert_eq!(
+            styles,
+            [
+                none().fg(Color::Red).bg(Color::Reset).bold(),
+                none().fg(Color::Red).bg(Color::Reset),
+                none().fg(Color::Reset).bg(Color::Reset),
+            ]
+        );
+        assert_eq!(buffer.content[7].modifier, Modifier::ITALIC);
+
+        // Lines given to a closure
+        let mut buffer = Buffer::empty(1, 2);
+        let mut c = buffer.canvas();
+        c.push_style(none().fg(Color::Red));
+        c.for_each_line(|_, mut line| {
+            line.draw_default("a");
+        });
+        assert!(buffer.content.iter().all(|c| c.fg == Color::Red));
+    }
+
+    #[test]
+    fn line_draw_box_char() {
+        let mut buffer = Buffer::empty(5, 3);
+        buffer.canvas().border(none());
+        let mut c = buffer.canvas();
+        // Dividers end with half chars to join the border with a T
+        let mut line = c.line_at(1);
+        line.draw_box_char('╶', none());
+        for _ in 0..3 {
+            line.draw_box_char('─', none());
+        }
+        line.draw_box_char('╴', none());
+        for (y, ch) in "╷│╵".chars().enumerate() {
+            c.line_at(y).skip(2).draw_box_char(ch, none());
+        }
+        assert_eq!(buffer, buf_lines(&["┌─┬─┐", "├─┼─┤", "└─┴─┘"]));
+    }
+
+    #[test]
+    fn line_rule() {
+        let mut buffer = Buffer::empty(20, 4);
+        let mut c = buffer.canvas();
+        c.top().rule("Section", '─', none());
+        c.top().rule("", '─', none());
+        c.top().skip(10).rule("Long section", '─', none());
+        c.top().skip(12).rule("表", '=', none());
+        assert_eq!(
+            buffer,
+            buf_lines(&[
+                "───── Section ──────",
+                "────────────────────",
+                "          ─ Long s ─",
+                "            == 表 ==",
+            ])
+        );
+
+        // Nothing visible to show
+        let mut buffer = Buffer::empty(1, 1);
+        buffer.canvas().top().rule("\u{200B}", '─', none());
+        assert_eq!(buffer.snapshot(), "─");
+    }
+
+    #[test]
+    fn canvas_vgauge() {
+        let mut buffer = buf_lines(&["abc"; 4]);
+        let mut c = buffer.canvas();
+        c.vgauge(0.25, none());
+        c.reserve_left(1);
+        c.vgauge(0.3, none());
+        c.reserve_left(1);
+        c.vgauge(1.0, none());
+        assert_eq!(buffer, buf_lines(&["  █", "  █", " ▂█", "███"]));
+    }
+
+    #[test]
+    fn canvas_scrollbar() {
+        let column = |total, visible, offset| {
+            let mut buffer = Buffer::empty(1, 10);
+            buffer.canvas().scrollbar(total, visible, offset, none());
+            buffer.snapshot().replace('\n', "")
+        };
+        assert_eq!(column(100, 10, 0), "█│││││││││");
+        assert_eq!(column(100, 10, 90), "│││││││││█");
+        assert_eq!(column(100, 10, 95), "│││││││││█");
+        assert_eq!(column(100, 30, 50), "│││││███││");
+        assert_eq!(column(1000, 1, 0), "█│││││││││");
+        assert_eq!(column(5, 10, 0), "██████████");
+    }
+
+    #[test]
+    fn canvas_image() {
+        let mut buffer = Buffer::empty(1, 1);
+        let pixels = [[(255, 0, 0), (0, 255, 0)], [(0, 0, 255), (9, 9, 9)]];
+        buffer.canvas().image(&pixels);
+        assert_eq!(
+            buffer.content[0].style(),
+            none().fg(rgb(255, 0, 0)).bg(rgb(0, 0, 255))
+        );
+        assert_eq!(buffer.snapshot(), "▀");
+
+        // Odd height
+        let mut buffer = Buffer::empty(2, 2);
+        buffer.canvas().image(&[[(1, 2, 3)]; 3]);
+        assert_eq!(buffer.snapshot(), "▀\n▀");
+        assert_eq!(
+            buffer.content[2].style(),
+            none().fg(rgb(1, 2, 3)).bg(Color::Reset)
+        );
+    }
+
+    #[test]
+    fn diff_clear_line() {
+        let prev = buf_lines(&["hello", "world"]);
+        let next = buf_lines(&["hi   ", "world"]);
+        assert_eq!(
+            prev.diff_with_clear(&next),
+            vec![
+                Change::Cell(1, 0, &cell('i')),
+                Change::ClearLine(2, 0, &Cell::default())
+            ]
+        );
+
+        // Never clear the right half of a wide char
+        let prev = buf_lines(&["abcde"]);
+        let mut next = Buffer::empty(5, 1);
+        next.canvas().line("中", none());
+        assert_eq!(
+            prev.diff_with_clear(&next),
+            vec![
+                Change::Cell(0, 0, &cell('中')),
+                Change::ClearLine(2, 0, &Cell::default())
+            ]
+        );
+    }
+
+    #[test]
+    fn canvas_line_at() {
+        let mut buffer = buf_lines(&["a", "b", "c", "d"]);
+        let mut c = buffer.canvas();
+        c.line_at(2).draw("X", none());
+        assert_eq!(c.line_at(4).width(), 0);
+        assert_eq!(c.height(), 4);
+        assert_eq!(buffer, buf_lines(&["a", "b", "X", "d"]));
+    }
+
+    #[test]
+    fn canvas_for_each_line() {
+        let mut buffer = Buffer::empty(3, 5);
+        let mut c = buffer.canvas();
+        c.reserve_top(1);
+        c.for_each_line(|i, mut line| {
+            line.draw(i, none());
+        });
+        assert_eq!(c.height(), 4);
+        assert_eq!(buffer, buf_lines(&["   ", "0  ", "1  ", "2  ", "3  "]));
+    }
+
+    #[test]
+    fn canvas_line_numbers() {
+        let mut buffer = Buffer::empty(5, 4);
+        let mut c = buffer.canvas();
+        c.line_numbers(98, Some(3), none());
+        c.line("a", none());
+        assert_eq!(buffer.snapshot(), " 98a\n 99\n100\n101");
+
+        let mut buffer = Buffer::empty(4, 4);
+        let mut c = buffer.canvas();
+        c.line_numbers(98, None, none()).line("a", none());
+        assert_eq!(c.width(), 1);
+        let mut buffer = Buffer::empty(4, 4);
+        buffer.canvas().line_numbers(98, Some(2), none());
+        assert_eq!(buffer.snapshot(), "98\n99\n00\n01");
+        assert_eq!(
+            buffer.content[0].style(),
+            cell(' ').set_style(none().dim()).style()
+        );
+
+        // Narrower than the numbers
+        let mut buffer = Buffer::empty(2, 4);
+        let mut c = buffer.canvas();
+        c.line_numbers(98, None, none()).line("a", none());
+        assert_eq!(c.width(), 0);
+        assert_eq!(buffer.snapshot(), "98\n99\n00\n01");
+    }
+
+    #[test]
+    fn canvas_dim_background() {
+        let mut buffer = Buffer::empty(4, 1);
+        let mut c = buffer.canvas();
+        c.line_at(0)
+            .draw("ab", none().bg(Color::Red))
+            .draw("c", none().bg(rgb(200, 100, 40)));
+        c.reserve_left(1);
+        c.dim_background(0.25);
+        let bgs: Vec<_> = buffer.content.iter().map(|c| c.bg).collect();
+        assert_eq!(
+            bgs,
+            vec![Color::Red, rgb(191, 0, 0), rgb(150, 75, 30), Color::Reset]
+        );
+    }
+
+    #[test]
+    fn canvas_fill() {
+        let mut buffer = Buffer::empty(5, 4);
+        let mut c = buffer.canvas();
+        c.consume(Area::new(1, 1, 3, 2));
+        c.fill('#', none());
+        assert_eq!(buffer, buf_lines(&["     ", " ### ", " ### ", "     "]));
+    }
+
+    #[test]
+    fn overlay() {
+        let field = ["....", "....", "....", "...."];
+        let popup = Frame(buf_lines(&["ab", "c "]));
+        let mut buffer = buf_lines(&field);
+        buffer.canvas().overlay(&popup, 1, 1);
+        assert_eq!(buffer, buf_lines(&["....", ".ab.", ".c..", "...."]));
+
+        // Clip at the destination bounds
+        let mut buffer = buf_lines(&field);
+        buffer.canvas().overlay(&popup, 3, 3);
+        assert_eq!(buffer, buf_lines(&["....", "....", "....", "...a"]));
+
+        // Wide char at the clip edge
+        let mut popup = Frame::new(2, 1);
+        popup.canvas().line("中", none());
+        let mut buffer = buf_lines(&field);
+        buffer.canvas().overlay(&popup, 3, 0);
+        assert_eq!(buffer, buf_lines(&["... ", "....", "....", "...."]));
+    }
+
+    #[test]
+    fn resize_keep_coordinates() {
+        let mut buffer = buf_lines(&["ab", "cd"]);
+        buffer.resize(3, 3);
+        assert_eq!(buffer, buf_lines(&["ab ", "cd ", "   "]));
+        buffer.resize(1, 2);
+        assert_eq!(buffer, buf_lines(&["a", "c"]));
+    }
+
+    #[test]
+    fn canvas_scroll() {
+        let mut buffer = buf_lines(&["A", "B", "C"]);
+        buffer.canvas().scroll_up(1);
+        assert_eq!(buffer, buf_lines(&["B", "C", " "]));
+
+        let mut buffer = buf_lines(&["A", "B", "C"]);
+        buffer.canvas().scroll_down(2);
+        assert_eq!(buffer, buf_lines(&[" ", " ", "A"]));
+
+        let mut buffer = buf_lines(&["A", "B", "C"]);
+        buffer.canvas().scroll_up(5);
+        assert_eq!(buffer, buf_lines(&[" ", " ", " "]));
+
+        // Only the sub area
+        let mut buffer = buf_lines(&["aA", "bB", "cC", "dD"]);
+        let mut c = buffer.canvas();
+        c.reserve_left(1);
+        c.reserve_top(1);
+        c.scroll_up(1);
+        assert_eq!(buffer, buf_lines(&["aA", "bC", "cD", "d "]));
+    }
+
+    #[test]
+    fn canvas_insert_delete_line() {
+        let mut buffer = buf_lines(&["A", "B", "C"]);
+        buffer.canvas().insert_line(1);
+        assert_eq!(buffer, buf_lines(&["A", " ", "B"]));
+        buffer.canvas().delete_line(0);
+        assert_eq!(buffer, buf_lines(&[" ", "B", " "]));
+
+        // Out of the area
+        buffer.canvas().insert_line(3).delete_line(3);
+        assert_eq!(buffer, buf_lines(&[" ", "B", " "]));
+
+        // Only the sub area
+        let mut buffer = buf_lines(&["aA", "bB", "cC", "dD"]);
+        let mut c = buffer.canvas();
+        c.reserve_left(1);
+        c.reserve_top(1);
+        c.delete_line(0).insert_line(2);
+        assert_eq!(buffer, buf_lines(&["aA", "bC", "cD", "d "]));
+        buffer.canvas().insert_line(2).insert_line(3);
+        assert_eq!(buffer, buf_lines(&["aA", "bC", "  ", "  "]));
+    }
+
+    #[test]
+    fn canvas_copy_region() {
+        let mut buffer = buf_lines(&["abcd", "efgh", "ijkl"]);
+        buffer.canvas().copy_region(Area::new(0, 0, 2, 2), 1, 1);
+        assert_eq!(buffer, buf_lines(&["abcd", "eabh", "iefl"]));
+
+        let mut buffer = buf_lines(&["abcd", "efgh", "ijkl"]);
+        buffer.canvas().copy_region(Area::new(1, 1, 2, 2), 0, 0);
+        assert_eq!(buffer, buf_lines(&["fgcd", "jkgh", "ijkl"]));
+
+        // Clipped at the buffer bounds
+        let mut buffer = buf_lines(&["abcd", "efgh", "ijkl"]);
+        buffer.canvas().copy_region(Area::new(0, 0, 2, 2), 3, 2);
+        assert_eq!(buffer, buf_lines(&["abcd", "efgh", "ijka"]));
     }
 
     #[test]
@@ -0,0 +1,303 @@
+use std::fmt;
+
+use super::style::{rgb, Color};
+
+/// Error returned when parsing an invalid hex color
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseColorError {
+    /// Expected 3 or 6 hex digits
+    InvalidLength(usize),
+    /// Not an hex digit
+    InvalidDigit(char),
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseColorError::InvalidLength(len) => {
+                write!(f, "invalid hex color length {len}, expected 3 or 6 digits")
+            }
+            ParseColorError::InvalidDigit(c) => write!(f, "invalid hex digit {c:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+/// Parse a `#rrggbb` or `#rgb` hex color, the hash being optional
+pub fn hex(s: &str) -> Result<Color, ParseColorError> {
+    let digits = s.strip_prefix('#').unwrap_or(s);
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(ParseColorError::InvalidDigit(c));
+    }
+    let value = |i: usize, n: usize| u8::from_str_radix(&digits[i..i + n], 16).unwrap();
+    match digits.len() {
+        3 => Ok(rgb(value(0, 1) * 17, value(1, 1) * 17, value(2, 1) * 17)),
+        6 => Ok(rgb(value(0, 2), value(2, 2), value(4, 2))),
+        len => Err(ParseColorError::InvalidLength(len)),
+    }
+}
+
+/// Shade of the 256 colors palette grayscale ramp, from 0 for the darkest to 23 for the
+/// lightest, higher levels being clamped
+pub fn gray(level: u8) -> Color {
+    Color::AnsiValue(232 + level.min(23))
+}
+
+/// Color of the 256 colors palette 6x6x6 cube, each component going from 0 to 5, higher values
+/// being clamped
+pub fn cube(r: u8, g: u8, b: u8) -> Color {
+    Color::AnsiValue(16 + 36 * r.min(5) + 6 * g.min(5) + b.min(5))
+}
+
+/// Number of colors supported by the terminal
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    /// 24-bit RGB colors
+    #[default]
+    TrueColor,
+    /// 256 colors palette
+    Ansi256,
+    /// 16 basic colors
+    Ansi16,
+}
+
+impl ColorDepth {
+    /// Nearest color supported with this depth
+    pub(crate) fn map(self, color: Color) -> Color {
+        match (self, color) {
+            (ColorDepth::Ansi256, Color::Rgb { r, g, b }) => Color::AnsiValue(nearest_256(r, g, b)),
+            (ColorDepth::Ansi16, Color::Rgb { .. } | Color::AnsiValue(16..)) => {
+                let (r, g, b) = to_rgb(color).unwrap();
+                BASIC[nearest(&BASIC_RGB, (r, g, b))]
+            }
+            _ => color,
+        }
+    }
+}
+
+/// The 16 basic colors in palette order
+pub(crate) const BASIC: [Color; 16] = [
+    Color::Black,
+    Color::DarkRed,
+    Color::DarkGreen,
+    Color::DarkYellow,
+    Color::DarkBlue,
+    Color::DarkMagenta,
+    Color::DarkCyan,
+    Color::Grey,
+    Color::DarkGrey,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+];
+
+/// Usual xterm values of the 16 basic colors, in palette order
+const BASIC_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Levels of each component in the 6x6x6 color cube
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// RGB value of a color, None for the terminal default
+pub(crate) fn to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    let index = match color {
+        Color::Reset => return None,
+        Color::Rgb { r, g, b } => return Some((r, g, b)),
+        Color::AnsiValue(n) => n,
+        Color::Black => 0,
+        Color::DarkRed => 1,
+        Color::DarkGreen => 2,
+        Color::DarkYellow => 3,
+        Color::DarkBlue => 4,
+        Color::DarkMagenta => 5,
+        Color::DarkCyan => 6,
+        Color::Grey => 7,
+        Color::DarkGrey => 8,
+        Color::Red => 9,
+        Color::Green => 10,
+        Color::Yellow => 11,
+        Color::Blue => 12,
+        Color::Magenta => 13,
+        Color::Cyan => 14,
+        Color::White => 15,
+    };
+    Some(match index {
+        0..=15 => BASIC_RGB[index as usize],
+        16..=231 => {
+            let i = index as usize - 16;
+            (
+                CUBE_LEVELS[i / 36],
+                CUBE_LEVELS[i / 6 % 6],
+                CUBE_LEVELS[i % 6],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    })
+}
+
+/// Mix `top` over `bottom` with an opacity from 0 to 1, palette colors being resolved to their
+/// usual RGB value and the terminal default to black
+pub fn blend(top: Color, bottom: Color, alpha: f64) -> Color {
+    let (top, bottom) = (
+        to_rgb(top).unwrap_or((0, 0, 0)),
+        to_rgb(bottom).unwrap_or((0, 0, 0)),
+    );
+    let alpha = alpha.clamp(0.0, 1.0);
+    let mix = |t: u8, b: u8| (t as f64 * alpha + b as f64 * (1.0 - alpha)).round() as u8;
+    rgb(
+        mix(top.0, bottom.0),
+        mix(top.1, bottom.1),
+        mix(top.2, bottom.2),
+    )
+}
+
+/// Move a color toward white by a percentage from 0 to 100
+pub fn lighten(color: Color, pct: f64) -> Color {
+    blend(rgb(255, 255, 255), color, pct / 100.0)
+}
+
+/// Move a color toward black by a percentage from 0 to 100
+pub fn darken(color: Color, pct: f64) -> Color {
+    blend(rgb(0, 0, 0), color, pct / 100.0)
+}
+
+/// Squared euclidean distance between two colors
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// Index of the nearest color of a palette
+fn nearest(palette: &[(u8, u8, u8)], color: (u8, u8, u8)) -> usize {
+    (0..palette.len())
+        .min_by_key(|i| distance(palette[*i], color))
+        .unwrap()
+}
+
+/// Nearest color of the 256 palette, from the color cube or the grayscale ramp
+pub(crate) fn nearest_256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |v: u8| nearest(&CUBE_LEVELS.map(|l| (l, 0, 0)), (v, 0, 0));
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+    let avg = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_index = (avg.saturating_sub(3) / 10).min(23) as u8;
+    let gray_level = 8 + 10 * gray_index;
+    let gray = (gray_level, gray_level, gray_level);
+    if distance(gray, (r, g, b)) < distance(cube, (r, g, b)) {
+        232 + gray_index
+    } else {
+        16 + 36 * ri as u8 + 6 * gi as u8 + bi as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex() {
+        let white = Color::Rgb {
+            r: 255,
+            g: 255,
+            b: 255,
+        };
+        assert_eq!(hex("#fff"), Ok(white));
+        assert_eq!(hex("#ffffff"), Ok(white));
+        assert_eq!(
+            hex("FF8800"),
+            Ok(Color::Rgb {
+                r: 255,
+                g: 136,
+                b: 0
+            })
+        );
+        assert_eq!(hex("#ff88"), Err(ParseColorError::InvalidLength(4)));
+        assert_eq!(hex("#ff880g"), Err(ParseColorError::InvalidDigit('g')));
+        assert_eq!(hex(""), Err(ParseColorError::InvalidLength(0)));
+    }
+
+    #[test]
+    fn palette_constructors() {
+        assert_eq!(gray(0), Color::AnsiValue(232));
+        assert_eq!(gray(23), Color::AnsiValue(255));
+        assert_eq!(gray(40), Color::AnsiValue(255));
+        assert_eq!(cube(0, 0, 0), Color::AnsiValue(16));
+        assert_eq!(cube(5, 0, 0), Color::AnsiValue(196));
+        assert_eq!(cube(5, 5, 5), Color::AnsiValue(231));
+        assert_eq!(cube(9, 0, 0), cube(5, 0, 0));
+    }
+
+    #[test]
+    fn palette_rgb() {
+        assert_eq!(to_rgb(Color::Reset), None);
+        assert_eq!(to_rgb(Color::Red), Some((255, 0, 0)));
+        assert_eq!(to_rgb(Color::AnsiValue(196)), Some((255, 0, 0)));
+        assert_eq!(to_rgb(Color::AnsiValue(110)), Some((135, 175, 215)));
+        assert_eq!(to_rgb(Color::AnsiValue(255)), Some((238, 238, 238)));
+    }
+
+    #[test]
+    fn nearest_palette() {
+        assert_eq!(nearest_256(255, 0, 0), 196);
+        assert_eq!(nearest_256(0, 0, 0), 16);
+        assert_eq!(nearest_256(255, 255, 255), 231);
+        assert_eq!(nearest_256(128, 128, 128), 244);
+        assert_eq!(nearest_256(135, 175, 215), 110);
+        assert_eq!(nearest_256(130, 170, 220), 110);
+
+        assert_eq!(
+            ColorDepth::Ansi256.map(rgb(255, 0, 0)),
+            Color::AnsiValue(196)
+        );
+        assert_eq!(ColorDepth::Ansi16.map(rgb(250, 10, 10)), Color::Red);
+        assert_eq!(ColorDepth::Ansi16.map(Color::AnsiValue(196)), Color::Red);
+        assert_eq!(
+            ColorDepth::Ansi16.map(Color::AnsiValue(3)),
+            Color::AnsiValue(3)
+        );
+        assert_eq!(ColorDepth::TrueColor.map(rgb(1, 2, 3)), rgb(1, 2, 3));
+    }
+
+    #[test]
+    fn blend_colors() {
+        assert_eq!(blend(Color::Red, Color::Black, 0.5), rgb(128, 0, 0));
+        assert_eq!(
+            blend(Color::AnsiValue(196), Color::Reset, 0.5),
+            rgb(128, 0, 0)
+        );
+        assert_eq!(blend(rgb(0, 0, 200), rgb(100, 0, 0), 0.25), rgb(75, 0, 50));
+        assert_eq!(blend(Color::White, Color::Black, 2.0), rgb(255, 255, 255));
+    }
+
+    #[test]
+    fn lighten_darken() {
+        assert_eq!(darken(Color::White, 50.0), hex("#808080").unwrap());
+        assert_eq!(lighten(Color::Black, 50.0), hex("#808080").unwrap());
+        assert_eq!(lighten(rgb(10, 20, 30), 0.0), rgb(10, 20, 30));
+        assert_eq!(darken(Color::AnsiValue(110), 100.0), rgb(0, 0, 0));
+    }
+}
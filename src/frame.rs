@@ -0,0 +1,226 @@
+#[cfg(feature = "crossterm")]
+use super::{
+    backend::{Backend, CrosstermBackend},
+    color::ColorDepth,
+    style::Attribute,
+    terminal::Pen,
+};
+use super::{
+    buffer::{Buffer, Cell},
+    color::to_rgb,
+    style::Modifier,
+    Canvas, Style,
+};
+
+/// A rendered frame, captured by `Terminal` recording or drawn on its own, for example as a
+/// popup to overlay with `Canvas::overlay`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame(pub(crate) Buffer);
+
+impl Frame {
+    /// Blank frame of `nb_col` columns and `nb_row` rows
+    pub fn new(nb_col: usize, nb_row: usize) -> Self {
+        Frame(Buffer::empty(nb_col, nb_row))
+    }
+
+    /// Canvas covering the whole frame
+    pub fn canvas(&mut self) -> Canvas<'_> {
+        self.0.canvas()
+    }
+
+    /// Number of columns
+    pub fn width(&self) -> usize {
+        self.0.nb_col
+    }
+
+    /// Number of rows
+    pub fn height(&self) -> usize {
+        self.0.nb_row
+    }
+
+    /// Char and style of a cell
+    pub fn cell_at(&self, x: usize, y: usize) -> Option<(&str, Style)> {
+        if x < self.0.nb_col && y < self.0.nb_row {
+            let cell = &self.0.content[self.0.index_of(x, y)];
+            Some((&cell.symbol, cell.style()))
+        } else {
+            None
+        }
+    }
+
+    /// Render as plain text, one line per row without trailing blanks
+    pub fn snapshot(&self) -> String {
+        self.0.snapshot()
+    }
+
+    /// Render as text colored with ANSI escape sequences, each line ending with a reset
+    #[cfg(feature = "crossterm")]
+    pub fn to_ansi(&self) -> String {
+        let mut out = CrosstermBackend::new(Vec::new());
+        let width = self.0.nb_col.max(1);
+        for (y, row) in self.0.content.chunks(width).enumerate() {
+            if y > 0 {
+                out.print("\n").unwrap();
+            }
+            let mut pen = Pen::new(ColorDepth::TrueColor);
+            let mut skip = false;
+            for cell in row {
+                if !std::mem::take(&mut skip) {
+                    pen.apply(&mut out, cell).unwrap();
+                    out.print(&cell.symbol).unwrap();
+                    skip = cell.width() > 1;
+                }
+            }
+            out.set_attribute(Attribute::Reset).unwrap();
+        }
+        out.flush().unwrap();
+        String::from_utf8(std::mem::take(out.writer())).unwrap()
+    }
+
+    /// Render as HTML, one `<div>` per row with inline styled spans. Reversed cells on a
+    /// default color use the `Canvas` and `CanvasText` system colors.
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        let width = self.0.nb_col.max(1);
+        for row in self.0.content.chunks(width) {
+            out.push_str("<div>");
+            let (mut style, mut text) = (String::new(), String::new());
+            let mut skip = false;
+            for cell in row {
+                if std::mem::take(&mut skip) {
+                    continue;
+                }
+                skip = cell.width() > 1;
+                let css = css(cell);
+                if css != style {
+                    push_run(&mut out, &style, &text);
+                    text.clear();
+                    style = css;
+                }
+                for c in cell.symbol.chars() {
+                    match c {
+                        '<' => text.push_str("&lt;"),
+                        '>' => text.push_str("&gt;"),
+                        '&' => text.push_str("&amp;"),
+                        c => text.push(c),
+                    }
+                }
+            }
+            push_run(&mut out, &style, &text);
+            out.push_str("</div>\n");
+        }
+        out
+    }
+}
+
+/// Inline CSS of a cell, empty for the default style
+fn css(cell: &Cell) -> String {
+    let mut css = String::new();
+    let hex = |color| to_rgb(color).map(|(r, g, b)| format!("#{r:02x}{g:02x}{b:02x}"));
+    let (fg, bg) = if cell.modifier.contains(Modifier::REVERSED) {
+        let fg = hex(cell.bg).unwrap_or("Canvas".into());
+        let bg = hex(cell.fg).unwrap_or("CanvasText".into());
+        (Some(fg), Some(bg))
+    } else {
+        (hex(cell.fg), hex(cell.bg))
+    };
+    if cell.modifier.contains(Modifier::HIDDEN) {
+        css.push_str("color:transparent;");
+    } else if let Some(fg) = fg {
+        css.push_str(&format!("color:{fg};"));
+    }
+    if let Some(bg) = bg {
+        css.push_str(&format!("background:{bg};"));
+    }
+    if cell.modifier.contains(Modifier::DIM) {
+        css.push_str("opacity:0.5;");
+    }
+    if cell.modifier.contains(Modifier::BOLD) {
+        css.push_str("font-weight:bold;");
+    }
+    if cell.modifier.contains(Modifier::ITALIC) {
+        css.push_str("font-style:italic;");
+    }
+    let decorations: Vec<_> = [
+        (Modifier::UNDERLINED, "underline"),
+        (Modifier::CROSSED_OUT, "line-through"),
+    ]
+    .into_iter()
+    .filter(|(m, _)| cell.modifier.contains(*m))
+    .map(|(_, name)| name)
+    .collect();
+    if !decorations.is_empty() {
+        css.push_str(&format!("text-decoration:{};", decorations.join(" ")));
+    }
+    css.pop();
+    css
+}
+
+/// Write a run of text, in a styled span if needed
+fn push_run(out: &mut String, css: &str, text: &str) {
+    if css.is_empty() {
+        out.push_str(text);
+    } else {
+        out.push_str(&format!("<span style=\"{css}\">{text}</span>"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{none, Color, Terminal};
+
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn ansi() {
+        let mut buf = Buffer::empty(2, 2);
+        buf.canvas()
+            .line("A", none().fg(Color::Red))
+            .line("中", none().bold());
+        assert_eq!(
+            Frame(buf).to_ansi(),
+            "\x1b[38;5;9mA\x1b[39m \x1b[0m\n\x1b[1m中\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn html() {
+        let mut buf = Buffer::empty(3, 2);
+        buf.canvas()
+            .line("A<", none().fg(Color::Red).bold())
+            .line("中&", none());
+        assert_eq!(
+            Frame(buf).to_html(),
+            "<div><span style=\"color:#ff0000;font-weight:bold\">A&lt;</span> </div>\n\
+             <div>中&amp;</div>\n"
+        );
+
+        let mut buf = Buffer::empty(4, 1);
+        buf.canvas()
+            .top()
+            .draw("a", none().fg(Color::Red).bg(Color::Blue).reversed())
+            .draw("b", none().reversed())
+            .draw("c", none().dim())
+            .draw("d", none().bg(Color::Red).hidden());
+        assert_eq!(
+            Frame(buf).to_html(),
+            "<div><span style=\"color:#5c5cff;background:#ff0000\">a</span>\
+             <span style=\"color:Canvas;background:CanvasText\">b</span>\
+             <span style=\"opacity:0.5\">c</span>\
+             <span style=\"color:transparent;background:#ff0000\">d</span></div>\n"
+        );
+    }
+
+    #[test]
+    fn overlay() {
+        let mut popup = Frame::new(2, 2);
+        popup.canvas().line("ab", none()).line("c", none());
+        let mut term = Terminal::test(4, 4);
+        term.draw(|c| {
+            c.fill('.', none()).overlay(&popup, 1, 1);
+        })
+        .unwrap();
+        assert_eq!(term.snapshot(), "....\n.ab.\n.c..\n....");
+    }
+}
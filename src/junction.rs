@@ -0,0 +1,52 @@
+const UP: u8 = 0b0001;
+const RIGHT: u8 = 0b0010;
+const DOWN: u8 = 0b0100;
+const LEFT: u8 = 0b1000;
+
+/// Light box-drawing glyph of each combination of segments
+const GLYPHS: [char; 16] = [
+    ' ', '╵', '╶', '└', '╷', '│', '┌', '├', '╴', '┘', '─', '┴', '┐', '┤', '┬', '┼',
+];
+
+/// Segments going out of the center of a light box-drawing char
+fn segments(c: char) -> Option<u8> {
+    let mask = match c {
+        '╭' => RIGHT | DOWN,
+        '╮' => LEFT | DOWN,
+        '╰' => UP | RIGHT,
+        '╯' => UP | LEFT,
+        c => GLYPHS.iter().skip(1).position(|g| *g == c)? as u8 + 1,
+    };
+    Some(mask)
+}
+
+/// Glyph drawing both `c` and the box-drawing char of `symbol`, `c` itself if either is not a
+/// light box-drawing char
+pub(crate) fn merge(symbol: &str, c: char) -> char {
+    let mut chars = symbol.chars();
+    let existing = match (chars.next(), chars.next()) {
+        (Some(existing), None) => existing,
+        _ => return c,
+    };
+    match (segments(existing), segments(c)) {
+        (Some(a), Some(b)) => GLYPHS[(a | b) as usize],
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_segments() {
+        assert_eq!(merge("─", '│'), '┼');
+        assert_eq!(merge("│", '─'), '┼');
+        assert_eq!(merge("┌", '┘'), '┼');
+        assert_eq!(merge("╭", '│'), '├');
+        assert_eq!(merge("─", '─'), '─');
+        assert_eq!(merge("a", '│'), '│');
+        assert_eq!(merge("─", 'a'), 'a');
+        assert_eq!(merge("", '│'), '│');
+    }
+}
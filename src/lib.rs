@@ -1,22 +1,48 @@
 use std::fmt;
 use std::fmt::{Display, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use unicode_segmentation::UnicodeSegmentation;
-use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use unicode_width::UnicodeWidthChar;
 
-use self::buffer::Buffer;
+use self::{buffer::Buffer, wrap::WrapLines};
 
+mod ansi;
+mod backend;
+mod braille;
 mod buffer;
+mod color;
+mod frame;
+mod junction;
+#[cfg(feature = "serde")]
+mod serialize;
 mod style;
+mod table;
 mod terminal;
+mod text;
+mod wrap;
 
+pub use ansi::{parse_ansi, strip_ansi};
+#[cfg(feature = "crossterm")]
+pub use backend::CrosstermBackend;
+pub use backend::{Backend, ClearType, HeadlessBackend, KeyboardEnhancementFlags};
+pub use braille::Braille;
+pub use color::{blend, cube, darken, gray, hex, lighten, ColorDepth, ParseColorError};
+#[cfg(feature = "crossterm")]
 pub use crossterm;
-pub use style::{none, Color, Style};
-pub use terminal::Terminal;
+#[cfg(feature = "crossterm")]
+pub use crossterm::event::{Event, MouseButton, MouseEvent, MouseEventKind};
+pub use frame::Frame;
+pub use style::{none, rgb, Attribute, Color, Modifier, Style};
+pub use table::{Align, Column, Table};
+pub use terminal::{CursorStyle, Terminal};
+pub use text::{Spans, Text};
 pub use unicode_segmentation;
 pub use unicode_width;
+pub use wrap::wrapped_height;
 
 /// A rectangular area
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct Area {
     x: usize,
     y: usize,
@@ -25,6 +51,58 @@ pub struct Area {
 }
 
 impl Area {
+    pub const fn new(x: usize, y: usize, w: usize, h: usize) -> Self {
+        Area { x, y, w, h }
+    }
+
+    /// Left column
+    pub const fn x(&self) -> usize {
+        self.x
+    }
+
+    /// Top line
+    pub const fn y(&self) -> usize {
+        self.y
+    }
+
+    /// Covered width
+    pub const fn width(&self) -> usize {
+        self.w
+    }
+
+    /// Covered height
+    pub const fn height(&self) -> usize {
+        self.h
+    }
+
+    /// Shrink by the same margin on every side
+    pub fn inner(&self, margin: usize) -> Area {
+        self.padded(margin, margin, margin, margin)
+    }
+
+    /// Shrink by a margin on each side, clamping to an empty area
+    pub fn padded(&self, top: usize, right: usize, btm: usize, left: usize) -> Area {
+        Area {
+            x: self.x + left.min(self.w),
+            y: self.y + top.min(self.h),
+            w: self.w.saturating_sub(left + right),
+            h: self.h.saturating_sub(top + btm),
+        }
+    }
+
+    /// Whether a point lies within the area
+    pub const fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x && x < self.x + self.w && y >= self.y && y < self.y + self.h
+    }
+
+    /// Overlapping region of two areas, None if they do not overlap
+    pub fn intersect(&self, other: &Area) -> Option<Area> {
+        let (x, y) = (self.x.max(other.x), self.y.max(other.y));
+        let end_x = (self.x + self.w).min(other.x + other.w);
+        let end_y = (self.y + self.h).min(other.y + other.h);
+        (x < end_x && y < end_y).then(|| Area::new(x, y, end_x - x, end_y - y))
+    }
+
     /// Consume top lines
     fn top(&mut self, h: usize) -> Area {
         let tmp = Area {
@@ -48,6 +126,30 @@ impl Area {
         self.h -= tmp.h;
         tmp
     }
+
+    /// Consume left columns
+    fn left(&mut self, w: usize) -> Area {
+        let tmp = Area {
+            w: w.min(self.w),
+            ..*self
+        };
+
+        self.x += tmp.w;
+        self.w -= tmp.w;
+        tmp
+    }
+
+    /// Consume right columns
+    fn right(&mut self, w: usize) -> Area {
+        let tmp = Area {
+            w: w.min(self.w),
+            x: self.x + self.w - w.min(self.w),
+            ..*self
+        };
+
+        self.w -= tmp.w;
+        tmp
+    }
 }
 
 /// Measure width of any display
@@ -56,7 +158,7 @@ pub fn width(text: impl Display) -> usize {
 
     impl fmt::Write for Measure {
         fn write_str(&mut self, s: &str) -> fmt::Result {
-            self.0 += s.width();
+            self.0 += str_width(s);
             Ok(())
         }
     }
@@ -66,27 +168,163 @@ pub fn width(text: impl Display) -> usize {
     measure.0
 }
 
+/// Columns taken by a char when drawn, zero-width and control chars taking none. Tabs are
+/// measured as 0 since lines expand them to the next tab stop.
+pub fn char_width(c: char) -> usize {
+    str_width(c.encode_utf8(&mut [0; 4]))
+}
+
+/// Columns taken by a grapheme when drawn, see `char_width`
+pub fn grapheme_width(g: &str) -> usize {
+    str_width(g)
+}
+
+/// Longest prefix fitting in `width` columns without splitting a grapheme, and whether the
+/// string was truncated
+pub fn truncate_to_width(s: &str, width: usize) -> (&str, bool) {
+    let (head, tail) = split_at_width(s, width);
+    (head, !tail.is_empty())
+}
+
+/// Pad with trailing spaces to `width` columns, truncating at a grapheme boundary if wider
+pub fn pad_right(s: &str, width: usize) -> String {
+    let (s, _) = truncate_to_width(s, width);
+    format!("{s}{:1$}", "", width - str_width(s))
+}
+
+/// Pad with leading spaces to `width` columns, truncating at a grapheme boundary if wider
+pub fn pad_left(s: &str, width: usize) -> String {
+    let (s, _) = truncate_to_width(s, width);
+    format!("{:1$}{s}", "", width - str_width(s))
+}
+
+/// How East Asian ambiguous-width characters such as `±` or `→` are measured
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WidthMode {
+    /// One column, like most terminals
+    #[default]
+    Standard,
+    /// Two columns, like terminals configured for CJK
+    Cjk,
+}
+
+/// Whether ambiguous-width characters are measured as wide
+static AMBIGUOUS_WIDE: AtomicBool = AtomicBool::new(false);
+
+/// Change how every width is measured, set it before drawing to match the terminal
+///
+/// ```
+/// use tui::{none, set_width_mode, width, Terminal, WidthMode};
+///
+/// assert_eq!(width("→±"), 2);
+/// let mut term = Terminal::test(4, 1);
+/// term.draw(|c| {
+///     c.line("→±→", none());
+/// })
+/// .unwrap();
+/// assert_eq!(term.snapshot(), "→±→");
+///
+/// set_width_mode(WidthMode::Cjk);
+/// assert_eq!(width("→±"), 4);
+/// term.draw(|c| {
+///     c.line("→±→", none());
+/// })
+/// .unwrap();
+/// assert_eq!(term.snapshot(), "→±");
+/// ```
+pub fn set_width_mode(mode: WidthMode) {
+    AMBIGUOUS_WIDE.store(mode == WidthMode::Cjk, Ordering::Relaxed);
+}
+
+/// Current width mode
+pub fn width_mode() -> WidthMode {
+    if AMBIGUOUS_WIDE.load(Ordering::Relaxed) {
+        WidthMode::Cjk
+    } else {
+        WidthMode::Standard
+    }
+}
+
+/// Printed width of a string, zero-width and control chars taking no space
+fn str_width(s: &str) -> usize {
+    if AMBIGUOUS_WIDE.load(Ordering::Relaxed) {
+        s.chars().map(|c| c.width_cjk().unwrap_or(0)).sum()
+    } else {
+        s.chars().map(|c| c.width().unwrap_or(0)).sum()
+    }
+}
+
+/// Split a string after its longest prefix fitting in `width` columns
+fn split_at_width(s: &str, width: usize) -> (&str, &str) {
+    let mut used = 0;
+    let end = s
+        .grapheme_indices(true)
+        .find(|(_, g)| {
+            used += str_width(g);
+            used > width
+        })
+        .map_or(s.len(), |(i, _)| i);
+    s.split_at(end)
+}
+
+/// Whether a char is a control or an invisible format char that can hide or reorder text, like
+/// zero-width spaces and bidi overrides. Joiners are kept as emoji sequences need them.
+fn is_invisible(c: char) -> bool {
+    c.is_control()
+        || matches!(
+            c,
+            '\u{61C}'
+                | '\u{180E}'
+                | '\u{200B}'
+                | '\u{200E}'..='\u{200F}'
+                | '\u{202A}'..='\u{202E}'
+                | '\u{2060}'..='\u{2064}'
+                | '\u{2066}'..='\u{206F}'
+                | '\u{FEFF}'
+                | '\u{FFF9}'..='\u{FFFB}'
+        )
+}
+
 /// Hidden write to format display text
 struct Writer<'a, 'b> {
     line: &'b mut Line<'a>,
     style: Style,
 }
 
+impl Writer<'_, '_> {
+    /// Put a grapheme known to fit
+    fn put(&mut self, g: &str, width: usize) {
+        self.line.buf.symbol_at(self.line.index, g, self.style);
+        self.line.index += width;
+        self.line.remaining = self.line.remaining.saturating_sub(width);
+    }
+}
+
 impl fmt::Write for Writer<'_, '_> {
     fn write_str(&mut self, s: &str) -> fmt::Result {
-        for c in s.chars() {
+        for g in s.graphemes(true) {
+            // Expand tabs to the next tab stop
+            if g == "\t" && self.line.tab_width > 0 {
+                let col = self.line.index - self.line.start;
+                let n = self.line.tab_width - col % self.line.tab_width;
+                for _ in 0..n.min(self.line.remaining) {
+                    self.put(" ", 1);
+                }
+                continue;
+            }
             // Check visible and fit
-            let width = c.width().unwrap_or(0);
+            let width = str_width(g);
             if width == 0 {
                 continue;
             } else if width > self.line.remaining {
+                // Do not leave a stale cell after a truncated wide char, the space stays available
+                if self.line.remaining > 0 {
+                    let Line { index, buf, .. } = self.line;
+                    buf.symbol_at(*index, " ", self.style);
+                }
                 break;
             }
-            // Put char
-            self.line.buf.char_at(self.line.index, c, self.style);
-            // Update state
-            self.line.index += width;
-            self.line.remaining = self.line.remaining.saturating_sub(width);
+            self.put(g, width);
         }
         Ok(())
     }
@@ -94,29 +332,46 @@ impl fmt::Write for Writer<'_, '_> {
 
 /// A canvas line
 pub struct Line<'a> {
+    start: usize,
     index: usize,
     remaining: usize,
+    tab_width: usize,
+    /// Style of the canvas when the line was taken
+    style: Style,
     buf: &'a mut Buffer,
 }
 
 impl<'a> Line<'a> {
     pub fn new(c: &'a mut Canvas, area: Area) -> Self {
         assert!(area.h <= 1);
-        if area.h > 0 {
-            Line {
-                index: c.buf.index_of(area.x, area.y),
-                remaining: area.w,
-                buf: c.buf,
-            }
+        let style = c.style();
+        // An empty area can start past the last column
+        let line = if area.h > 0 && area.w > 0 {
+            Line::span(c.buf.index_of(area.x, area.y), area.w, c.buf)
         } else {
-            Line {
-                index: 0,
-                remaining: 0,
-                buf: c.buf,
-            }
+            Line::span(0, 0, c.buf)
+        };
+        Line { style, ..line }
+    }
+
+    /// Line covering `remaining` cells from `index`
+    fn span(index: usize, remaining: usize, buf: &'a mut Buffer) -> Self {
+        Line {
+            start: index,
+            index,
+            remaining,
+            tab_width: 8,
+            style: none(),
+            buf,
         }
     }
 
+    /// Change the distance between tab stops, 0 dropping tabs
+    pub fn tab_width(&mut self, tab_width: usize) -> &mut Self {
+        self.tab_width = tab_width;
+        self
+    }
+
     /// Write styled text at the beginning of the line
     pub fn draw(&mut self, text: impl fmt::Display, style: Style) -> &mut Self {
         Writer { line: self, style }
@@ -125,8 +380,136 @@ impl<'a> Line<'a> {
         self
     }
 
+    /// Write text with the style of the canvas, see `Canvas::push_style`
+    pub fn draw_default(&mut self, text: impl fmt::Display) -> &mut Self {
+        self.draw(text, self.style)
+    }
+
+    /// Write a sequence of styled texts, stopping at the first one that does not fit
+    pub fn draw_spans(&mut self, spans: &[(impl AsRef<str>, Style)]) -> &mut Self {
+        for (text, style) in spans {
+            let truncated = str_width(text.as_ref()) > self.remaining;
+            self.draw(text.as_ref(), *style);
+            if truncated {
+                break;
+            }
+        }
+        self
+    }
+
+    /// Write a single styled char
+    pub fn draw_char(&mut self, c: char, style: Style) -> &mut Self {
+        Writer { line: self, style }.write_char(c).unwrap();
+        self
+    }
+
+    /// Write a single styled char, a light box-drawing char being merged with the one already in
+    /// the cell into the junction of both, like `─` over `│` giving `┼`. Half chars like `╶`
+    /// give T-junctions, `╶` over `│` giving `├`.
+    pub fn draw_box_char(&mut self, c: char, style: Style) -> &mut Self {
+        if self.remaining == 0 {
+            return self;
+        }
+        let c = junction::merge(&self.buf.content[self.index].symbol, c);
+        self.draw_char(c, style)
+    }
+
+    /// Write styled text, every occurrence of `needle` ignoring ASCII case being drawn with `hl`
+    pub fn draw_highlighted(
+        &mut self,
+        text: &str,
+        needle: &str,
+        base: Style,
+        hl: Style,
+    ) -> &mut Self {
+        // Mark matched bytes, matches can overlap
+        let mut matched = vec![false; text.len()];
+        if !needle.is_empty() {
+            for (i, _) in text.char_indices() {
+                let candidate = text.as_bytes()[i..].get(..needle.len());
+                if candidate.is_some_and(|c| c.eq_ignore_ascii_case(needle.as_bytes())) {
+                    matched[i..i + needle.len()].fill(true);
+                }
+            }
+        }
+        // Draw runs of whole graphemes
+        let mut runs = Vec::new();
+        let mut start = 0;
+        let mut highlighted = false;
+        for (i, g) in text.grapheme_indices(true) {
+            let hit = matched[i..i + g.len()].contains(&true);
+            if hit != highlighted {
+                runs.push((&text[start..i], if highlighted { hl } else { base }));
+                (start, highlighted) = (i, hit);
+            }
+        }
+        runs.push((&text[start..], if highlighted { hl } else { base }));
+        self.draw_spans(&runs)
+    }
+
+    /// Write styled text showing control and invisible format chars, in caret notation like `^A`
+    /// for C0 controls and `^?` for DEL, or as an escape like `\u{85}` or `\u{200b}` for others,
+    /// dimmed
+    pub fn draw_visible(&mut self, text: &str, style: Style) -> &mut Self {
+        let mut rest = text;
+        while let Some(i) = rest.find(is_invisible) {
+            let (visible, tail) = rest.split_at(i);
+            self.draw(visible, style);
+            let c = tail.chars().next().unwrap();
+            let escaped = match c as u32 {
+                code @ 0..=0x1f => format!("^{}", char::from(b'@' + code as u8)),
+                0x7f => "^?".into(),
+                code => format!("\\u{{{code:x}}}"),
+            };
+            self.draw(escaped, style.dim());
+            rest = &tail[c.len_utf8()..];
+        }
+        self.draw(rest, style)
+    }
+
+    /// Advance by `n` columns leaving the skipped cells untouched.
+    ///
+    /// Skipping into the middle of a double-width char keeps its left half, drawing over its right
+    /// half afterward will corrupt it.
+    pub fn skip(&mut self, n: usize) -> &mut Self {
+        let n = n.min(self.remaining);
+        self.index += n;
+        self.remaining -= n;
+        self
+    }
+
+    /// Write styled text as a hyperlink to `url`
+    pub fn draw_link(&mut self, text: impl fmt::Display, url: &str, style: Style) -> &mut Self {
+        let start = self.index;
+        self.draw(text, style);
+        let url: Arc<str> = Arc::from(url);
+        for cell in &mut self.buf.content[start..self.index] {
+            cell.link = Some(url.clone());
+        }
+        self
+    }
+
     /// Write a formatted text at the end of the line
     pub fn rdraw(&mut self, text: impl fmt::Display, style: Style) -> &mut Self {
+        // When the text overflows we keep its tail, dropping leading graphemes until
+        // it fits without splitting a wide one
+        if width(&text) > self.remaining {
+            let text = text.to_string();
+            let remaining = self.remaining;
+            let mut tail = 0;
+            let start = text
+                .grapheme_indices(true)
+                .rev()
+                .take_while(|(_, g)| {
+                    tail += str_width(g);
+                    tail <= remaining
+                })
+                .last()
+                .map_or(text.len(), |(i, _)| i);
+            self.rdraw(&text[start..], style);
+            self.remaining = 0;
+            return self;
+        }
         // We cannot simply write str from right to left as write_ftm is going to split
         // the text into multiple string from left to right, the workaround is to
         // 1: compute the printed length
@@ -134,9 +517,8 @@ impl<'a> Line<'a> {
         let fit = width.min(self.remaining);
         // 2: create a dummy line containing the required end of the current line
         Line {
-            index: self.index + self.remaining - fit,
-            remaining: fit,
-            buf: self.buf,
+            tab_width: self.tab_width,
+            ..Line::span(self.index + self.remaining - fit, fit, self.buf)
         }
         .draw(text, style);
         // 3: apply the lost end part manually
@@ -144,10 +526,143 @@ impl<'a> Line<'a> {
         self
     }
 
+    /// Write styled text, replacing its overflowing end with an ellipsis
+    pub fn draw_truncated(
+        &mut self,
+        text: impl fmt::Display,
+        style: Style,
+        ellipsis: &str,
+    ) -> &mut Self {
+        if width(&text) <= self.remaining {
+            return self.draw(text, style);
+        }
+        let text = text.to_string();
+        let available = self.remaining.saturating_sub(str_width(ellipsis));
+        let (head, _) = split_at_width(&text, available);
+        self.draw(head, style).draw(ellipsis, style)
+    }
+
+    /// Write a formatted text centered in the remaining space of the line
+    pub fn cdraw(&mut self, text: impl fmt::Display, style: Style) -> &mut Self {
+        let pad = self.remaining.saturating_sub(width(&text));
+        // Odd cell goes to the right like in SplitBuilder::apply
+        let (left, right) = (pad / 2, pad / 2 + pad % 2);
+        self.draw(format_args!("{:left$}", ""), style)
+            .draw(text, style)
+            .draw(format_args!("{:right$}", ""), style)
+    }
+
+    /// Write a left, right and centered text, the center taking the space in between
+    pub fn three(
+        &mut self,
+        left: impl fmt::Display,
+        center: impl fmt::Display,
+        right: impl fmt::Display,
+        style: Style,
+    ) -> &mut Self {
+        self.draw(left, style)
+            .rdraw(right, style)
+            .cdraw(center, style)
+    }
+
+    /// Write text with a foreground going from `from` on its first grapheme to `to` on its last
+    /// drawn one, zero width graphemes being skipped
+    pub fn gradient(&mut self, text: impl fmt::Display, from: Color, to: Color) -> &mut Self {
+        let text = text.to_string();
+        let glyphs: Vec<_> = text.graphemes(true).filter(|g| str_width(g) > 0).collect();
+        let mut used = 0;
+        let fit = glyphs
+            .iter()
+            .take_while(|g| {
+                used += str_width(g);
+                used <= self.remaining
+            })
+            .count();
+        let last = fit.saturating_sub(1).max(1) as f64;
+        for (i, g) in glyphs[..fit].iter().enumerate() {
+            self.draw(g, none().fg(blend(to, from, i as f64 / last)));
+        }
+        if let Some(g) = glyphs.get(fit) {
+            // Blank the cell left by a wide glyph that does not fit
+            self.draw(g, none().fg(to));
+        }
+        self
+    }
+
+    /// Fill the remaining space with a horizontal gauge, using eighth blocks for the fractional
+    /// part and styled spaces for the empty part
+    pub fn gauge(&mut self, ratio: f64, style: Style) -> &mut Self {
+        const PARTIALS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+        let eighths = (ratio.clamp(0.0, 1.0) * self.remaining as f64 * 8.0).round() as usize;
+        for _ in 0..eighths / 8 {
+            self.draw_char('█', style);
+        }
+        if let Some(partial) = (eighths % 8).checked_sub(1) {
+            self.draw_char(PARTIALS[partial], style);
+        }
+        self.fill(style)
+    }
+
+    /// Fill the remaining space with a rule of `rule_char`, a label being centered in it between
+    /// spaces. The label is truncated to keep at least one rule char on each side.
+    pub fn rule(&mut self, label: impl fmt::Display, rule_char: char, style: Style) -> &mut Self {
+        let label = label.to_string();
+        let (label, _) = truncate_to_width(&label, self.remaining.saturating_sub(4));
+        if str_width(label) == 0 {
+            return self.fill_char(rule_char, style);
+        }
+        let pad = self.remaining - str_width(label) - 2;
+        for _ in 0..pad / 2 {
+            self.draw_char(rule_char, style);
+        }
+        self.draw(format_args!(" {label} "), style)
+            .fill_char(rule_char, style)
+    }
+
+    /// Draw a block per value scaled between the smallest and largest values, from the start of
+    /// the line. Values are grouped by their maximum when they outnumber the columns.
+    pub fn sparkline(&mut self, data: &[u64], style: Style) -> &mut Self {
+        const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+        let (Some(&min), Some(&max)) = (data.iter().min(), data.iter().max()) else {
+            return self;
+        };
+        let n = data.len().min(self.remaining);
+        for i in 0..n {
+            let bucket = &data[i * data.len() / n..(i + 1) * data.len() / n];
+            let value = bucket.iter().copied().max().unwrap_or(min);
+            let level = match max - min {
+                0 => 3,
+                range => ((value - min) as u128 * 7 / range as u128) as usize,
+            };
+            self.draw_char(BLOCKS[level], style);
+        }
+        self
+    }
+
+    /// Fill the remaining space of the line with styled spaces
+    pub fn fill(&mut self, style: Style) -> &mut Self {
+        self.fill_char(' ', style)
+    }
+
+    /// Fill the remaining space of the line with a repeated styled char
+    pub fn fill_char(&mut self, c: char, style: Style) -> &mut Self {
+        let mut writer = Writer { line: self, style };
+        for _ in 0..writer.line.remaining {
+            writer.write_char(c).unwrap();
+        }
+        self.remaining = 0;
+        self
+    }
+
     pub fn width(&self) -> usize {
         self.remaining
     }
 
+    /// Columns advanced from the start of the line, text drawn from the end not included
+    pub fn used(&self) -> usize {
+        self.index - self.start
+    }
+
     /// Place cursor where we are on the line
     pub fn cursor(&mut self) -> &mut Self {
         self.buf.cursor_pos = Some(self.buf.pos_of(self.index));
@@ -156,7 +671,7 @@ impl<'a> Line<'a> {
 
     /// Check text fit in remaining space
     pub fn fit(&self, str: impl AsRef<str>) -> bool {
-        str.as_ref().width() < self.remaining
+        str_width(str.as_ref()) <= self.remaining
     }
 }
 
@@ -164,6 +679,10 @@ impl<'a> Line<'a> {
 pub struct Canvas<'a> {
     area: Area,
     buf: &'a mut Buffer,
+    /// Style inherited from the parent canvas
+    base: Style,
+    /// Effective styles pushed on this canvas
+    styles: Vec<Style>,
 }
 
 impl<'a> Canvas<'a> {
@@ -173,20 +692,70 @@ impl<'a> Canvas<'a> {
         self
     }
 
+    /// Canvas scoped to an area such as a reserved one, clipped to the buffer, leaving this one
+    /// untouched
+    pub fn sub(&mut self, area: Area) -> Canvas<'_> {
+        let bounds = Area::new(0, 0, self.buf.nb_col, self.buf.nb_row);
+        Canvas {
+            area: area.intersect(&bounds).unwrap_or_default(),
+            base: self.style(),
+            buf: self.buf,
+            styles: Vec::new(),
+        }
+    }
+
+    /* ----- Styles ----- */
+
+    /// Patch the current style with `style` until the matching `pop_style`, sub canvases
+    /// inheriting it
+    pub fn push_style(&mut self, style: Style) -> &mut Self {
+        self.styles.push(self.style().patch(style));
+        self
+    }
+
+    /// Restore the style in place before the last `push_style` on this canvas
+    pub fn pop_style(&mut self) -> &mut Self {
+        self.styles.pop();
+        self
+    }
+
+    /// Current style, the pushed styles patched on top of each other
+    pub fn style(&self) -> Style {
+        self.styles.last().copied().unwrap_or(self.base)
+    }
+
     /* ----- Lines ----- */
 
     /// Get first line
-    pub fn top(&mut self) -> Line {
+    pub fn top(&mut self) -> Line<'_> {
         let area = self.area.top(1);
         Line::new(self, area)
     }
 
     /// Get last line
-    pub fn btm(&mut self) -> Line {
+    pub fn btm(&mut self) -> Line<'_> {
         let area = self.area.btm(1);
         Line::new(self, area)
     }
 
+    /// Get the line at offset `y` without consuming it, empty when out of range
+    pub fn line_at(&mut self, y: usize) -> Line<'_> {
+        let mut area = self.area;
+        area.top(y);
+        let area = area.top(1);
+        Line::new(self, area)
+    }
+
+    /// Call `f` with the index and line of each row from top to bottom, leaving the area
+    /// untouched
+    pub fn for_each_line(&mut self, mut f: impl FnMut(usize, Line<'_>)) -> &mut Self {
+        for y in 0..self.area.h {
+            let area = Area::new(self.area.x, self.area.y + y, self.area.w, 1);
+            f(y, Line::new(self, area));
+        }
+        self
+    }
+
     /* ----- Utils ----- */
 
     pub fn line(&mut self, text: impl Display, style: Style) -> &mut Self {
@@ -199,23 +768,292 @@ impl<'a> Canvas<'a> {
         self
     }
 
-    /// Write multilines a the top, wrapping to avoid splitting word
-    pub fn wrap(&mut self, string: impl AsRef<str>, style: Style) {
-        let mut words = string.as_ref().split_word_bounds().peekable();
-        for _ in 0..self.area.h {
+    /// Write styled lines at the top
+    pub fn draw_text(&mut self, text: &Text) -> &mut Self {
+        for spans in &text.0 {
+            self.top().draw_spans(&spans.0);
+        }
+        self
+    }
+
+    /// Write multilines a the top, wrapping to avoid splitting word and breaking at newlines.
+    /// Returns the number of lines used.
+    pub fn wrap(&mut self, string: impl AsRef<str>, style: Style) -> usize {
+        let lines = WrapLines::new(string.as_ref(), self.area.w).take(self.area.h);
+        let mut count = 0;
+        for segments in lines {
+            let mut line = self.top();
+            for segment in segments {
+                line.draw(segment, style);
+            }
+            count += 1;
+        }
+        count
+    }
+
+    /// Like `wrap` but starting the continuation lines of each paragraph `indent` columns in.
+    /// Returns the number of lines used.
+    pub fn wrap_indented(&mut self, string: impl AsRef<str>, style: Style, indent: usize) -> usize {
+        let (width, height) = (self.area.w, self.area.h);
+        let mut lines = WrapLines::new(string.as_ref(), width);
+        let mut count = 0;
+        let mut continuation = false;
+        while let Some(segments) = lines.next().filter(|_| count < height) {
+            let mut line = self.top();
+            count += 1;
+            if continuation {
+                line.skip(indent);
+            }
+            for segment in segments {
+                line.draw(segment, style);
+            }
+            continuation = !lines.ends_paragraph();
+            lines.set_width(if continuation {
+                width.saturating_sub(indent)
+            } else {
+                width
+            });
+        }
+        count
+    }
+
+    /// Like `wrap` but padding the gaps between words so that each line fills the width, except
+    /// the last line of a paragraph and lines of a single word. Returns the number of lines used.
+    pub fn wrap_justified(&mut self, string: impl AsRef<str>, style: Style) -> usize {
+        let (width, height) = (self.area.w, self.area.h);
+        let mut lines = WrapLines::new(string.as_ref(), width);
+        let mut count = 0;
+        while let Some(segments) = lines.next().filter(|_| count < height) {
             let mut line = self.top();
-            loop {
-                if let Some(next) = words.peek() {
-                    if line.fit(next) {
-                        line.draw(words.next().unwrap(), style);
-                    } else {
-                        break;
-                    }
-                } else {
-                    return;
+            count += 1;
+            let text = segments.concat();
+            let words: Vec<_> = text.split_whitespace().collect();
+            if lines.ends_paragraph() || words.len() < 2 {
+                for segment in segments {
+                    line.draw(segment, style);
+                }
+                continue;
+            }
+            // Keep the indentation, spread the remaining space with extra to the left gaps
+            let indent = &text[..text.len() - text.trim_start().len()];
+            let used: usize = str_width(indent) + words.iter().map(|w| str_width(w)).sum::<usize>();
+            let space = width.saturating_sub(used);
+            let gaps = words.len() - 1;
+            line.draw(indent, style).draw(words[0], style);
+            for (i, word) in words[1..].iter().enumerate() {
+                let gap = space / gaps + usize::from(i < space % gaps);
+                line.draw(format_args!("{:gap$}", ""), style)
+                    .draw(word, style);
+            }
+        }
+        count
+    }
+
+    /// Draw a border around the area then shrink it to the interior
+    pub fn border(&mut self, style: Style) -> &mut Self {
+        if self.area.w < 2 || self.area.h < 2 {
+            return self;
+        }
+        self.top()
+            .draw('┌', style)
+            .rdraw('┐', style)
+            .fill_char('─', style);
+        self.btm()
+            .draw('└', style)
+            .rdraw('┘', style)
+            .fill_char('─', style);
+        let (left, right) = (self.area.left(1), self.area.right(1));
+        for y in self.area.y..self.area.y + self.area.h {
+            self.buf.char_at(self.buf.index_of(left.x, y), '│', style);
+            self.buf.char_at(self.buf.index_of(right.x, y), '│', style);
+        }
+        self
+    }
+
+    /// Draw a border with a title embedded in its top edge then shrink to the interior
+    pub fn border_titled(&mut self, title: impl Display, style: Style) -> &mut Self {
+        let area = self.area;
+        self.border(style);
+        // Keep a corner and a box char on each side of the title
+        if area.w > 4 && area.h >= 2 {
+            let index = self.buf.index_of(area.x + 2, area.y);
+            Line::span(index, area.w - 4, self.buf).draw(title, style);
+        }
+        self
+    }
+
+    /// Fill every cell with a styled char, leaving the area untouched
+    pub fn fill(&mut self, c: char, style: Style) -> &mut Self {
+        for y in self.area.y..self.area.y + self.area.h {
+            let area = Area::new(self.area.x, y, self.area.w, 1);
+            Line::new(self, area).fill_char(c, style);
+        }
+        self
+    }
+
+    /// Reserve a left gutter numbering each row from `first`, right aligned and dimmed. The
+    /// gutter is as wide as the largest number unless a width is given, wider numbers keeping
+    /// their last digits. It never takes more than the canvas width.
+    pub fn line_numbers(&mut self, first: usize, width: Option<usize>, style: Style) -> &mut Self {
+        let last = first + self.area.h.saturating_sub(1);
+        let width = width
+            .unwrap_or_else(|| last.to_string().len())
+            .min(self.area.w);
+        let gutter = self.reserve_left(width);
+        let mut gutter = self.sub(gutter);
+        for n in first..=last {
+            gutter.top().rdraw(n, style.dim());
+        }
+        self
+    }
+
+    /// Write text vertically down the left column, one grapheme per line
+    pub fn vline_text(&mut self, text: impl AsRef<str>, style: Style) {
+        if self.area.w == 0 {
+            return;
+        }
+        let graphemes = text.as_ref().graphemes(true);
+        for (y, g) in (self.area.y..self.area.y + self.area.h).zip(graphemes) {
+            self.buf
+                .symbol_at(self.buf.index_of(self.area.x, y), g, style);
+        }
+    }
+
+    /// Fill the left column with a vertical gauge from the bottom, using eighth blocks for the
+    /// fractional part and styled spaces for the empty part
+    pub fn vgauge(&mut self, ratio: f64, style: Style) {
+        const PARTIALS: [char; 7] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇'];
+        if self.area.w == 0 {
+            return;
+        }
+        let mut eighths = (ratio.clamp(0.0, 1.0) * self.area.h as f64 * 8.0).round() as usize;
+        for y in (self.area.y..self.area.y + self.area.h).rev() {
+            let c = match eighths {
+                0 => ' ',
+                1..=7 => PARTIALS[eighths - 1],
+                _ => '█',
+            };
+            eighths = eighths.saturating_sub(8);
+            self.buf
+                .char_at(self.buf.index_of(self.area.x, y), c, style);
+        }
+    }
+
+    /// Fill the left column with a scrollbar, a `█` thumb sized and placed in a `│` track as the
+    /// `visible` rows starting at `offset` are in the `total` ones. The thumb is at least one
+    /// cell tall.
+    pub fn scrollbar(&mut self, total: usize, visible: usize, offset: usize, style: Style) {
+        let h = self.area.h;
+        if self.area.w == 0 || h == 0 {
+            return;
+        }
+        let total = total.max(visible).max(1);
+        let size = (visible * h).div_ceil(total).clamp(1, h);
+        let start = (offset * h / total).min(h - size);
+        for y in 0..h {
+            let c = if (start..start + size).contains(&y) {
+                '█'
+            } else {
+                '│'
+            };
+            let index = self.buf.index_of(self.area.x, self.area.y + y);
+            self.buf.char_at(index, c, style);
+        }
+    }
+
+    /// Draw rows of RGB pixels from the top left, each cell showing two stacked pixels with a
+    /// `▀` colored by the top one over the bottom one, clipping at the area bounds. The last
+    /// row of an odd height image leaves the background untouched.
+    pub fn image(&mut self, pixels: &[impl AsRef<[(u8, u8, u8)]>]) -> &mut Self {
+        for (y, rows) in pixels.chunks(2).take(self.area.h).enumerate() {
+            let top = rows[0].as_ref();
+            let btm = rows.get(1).map(|r| r.as_ref());
+            for (x, &(r, g, b)) in top.iter().take(self.area.w).enumerate() {
+                let mut style = none().rgb_fg(r, g, b);
+                if let Some(&(r, g, b)) = btm.and_then(|row| row.get(x)) {
+                    style = style.rgb_bg(r, g, b);
+                }
+                let index = self.buf.index_of(self.area.x + x, self.area.y + y);
+                self.buf.char_at(index, '▀', style);
+            }
+        }
+        self
+    }
+
+    /// Darken the background of every cell as if covered by black with an opacity from 0 to 1,
+    /// for example behind a popup. Cells on the terminal default background are left alone as
+    /// its color is unknown.
+    pub fn dim_background(&mut self, alpha: f64) -> &mut Self {
+        for y in self.area.y..self.area.y + self.area.h {
+            for x in self.area.x..self.area.x + self.area.w {
+                let i = self.buf.index_of(x, y);
+                let cell = &mut self.buf.content[i];
+                if cell.bg != Color::Reset {
+                    cell.bg = blend(Color::Black, cell.bg, alpha);
                 }
             }
         }
+        self
+    }
+
+    /// Shift the lines up by `n`, blanking the exposed lines at the bottom
+    pub fn scroll_up(&mut self, n: usize) -> &mut Self {
+        self.buf.scroll_up(&self.area, n);
+        self
+    }
+
+    /// Shift the lines down by `n`, blanking the exposed lines at the top
+    pub fn scroll_down(&mut self, n: usize) -> &mut Self {
+        self.buf.scroll_down(&self.area, n);
+        self
+    }
+
+    /// Insert a blank line at row `y` of the area, pushing the lines below down and dropping the
+    /// last one. Nothing happens if `y` is outside the area.
+    pub fn insert_line(&mut self, y: usize) -> &mut Self {
+        if let Some(below) = self.below(y) {
+            self.buf.scroll_down(&below, 1);
+        }
+        self
+    }
+
+    /// Delete the line at row `y` of the area, pulling the lines below up and leaving the last
+    /// one blank. Nothing happens if `y` is outside the area.
+    pub fn delete_line(&mut self, y: usize) -> &mut Self {
+        if let Some(below) = self.below(y) {
+            self.buf.scroll_up(&below, 1);
+        }
+        self
+    }
+
+    /// Part of the area from row `y` down
+    fn below(&self, y: usize) -> Option<Area> {
+        let Area { x, w, h, .. } = self.area;
+        (y < h).then(|| Area::new(x, self.area.y + y, w, h - y))
+    }
+
+    /// Copy the cells of a buffer area at the buffer coordinates `(x, y)`, clipping at the
+    /// buffer bounds. The areas can overlap.
+    pub fn copy_region(&mut self, src: Area, x: usize, y: usize) -> &mut Self {
+        self.buf.copy_region(&src, x, y);
+        self
+    }
+
+    /// Copy the non blank cells of a frame at `(x, y)`, clipping at this canvas bounds
+    pub fn overlay(&mut self, frame: &Frame, x: usize, y: usize) -> &mut Self {
+        let src = Area::new(0, 0, frame.0.nb_col, frame.0.nb_row);
+        self.buf.overlay(&frame.0, &src, &self.area, x, y);
+        self
+    }
+
+    /// Read back the char and style of a cell at buffer coordinates
+    pub fn cell_at(&self, x: usize, y: usize) -> Option<(&str, Style)> {
+        if x < self.buf.nb_col && y < self.buf.nb_row {
+            let cell = &self.buf.content[self.buf.index_of(x, y)];
+            Some((&cell.symbol, cell.style()))
+        } else {
+            None
+        }
     }
 
     /* ----- Area ----- */
@@ -248,6 +1086,16 @@ impl<'a> Canvas<'a> {
     pub fn reserve_btm(&mut self, n: usize) -> Area {
         self.area.btm(n)
     }
+
+    /// Reserve left columns
+    pub fn reserve_left(&mut self, n: usize) -> Area {
+        self.area.left(n)
+    }
+
+    /// Reserve right columns
+    pub fn reserve_right(&mut self, n: usize) -> Area {
+        self.area.right(n)
+    }
 }
 
 pub struct SplitBuilder {
@@ -268,9 +1116,106 @@ impl SplitBuilder {
     }
 
     pub fn apply(self) -> (Area, Area) {
+        let space = self.space();
+        self.cut(space / 2)
+    }
+
+    /// Split according to a ratio, the first area getting `first / (first + second)` of the space
+    pub fn ratio(self, first: u32, second: u32) -> (Area, Area) {
+        let total = first as u64 + second as u64;
+        if total == 0 {
+            return self.apply();
+        }
+        let space = self.space();
+        self.cut((space as u64 * first as u64 / total) as usize)
+    }
+
+    /// Split with the first area getting exactly `n` cells and the second the rest
+    pub fn fixed_first(self, n: usize) -> (Area, Area) {
+        let space = self.space();
+        self.cut(n.min(space))
+    }
+
+    /// Split with the second area getting exactly `n` cells and the first the rest
+    pub fn fixed_second(self, n: usize) -> (Area, Area) {
+        let space = self.space();
+        self.cut(space - n.min(space))
+    }
+
+    /// Divide into a `rows` by `cols` matrix of areas, remainders going to the first ones and
+    /// `gap` separating them in both directions
+    pub fn grid(self, rows: usize, cols: usize) -> Vec<Vec<Area>> {
+        if rows == 0 || cols == 0 {
+            return vec![];
+        }
+        let area = self.area;
+        let rows = place(area.h, &even(area.h, rows, self.gap), self.gap);
+        let cols = place(area.w, &even(area.w, cols, self.gap), self.gap);
+        rows.iter()
+            .map(|&(y, h)| {
+                cols.iter()
+                    .map(|&(x, w)| Area::new(area.x + x, area.y + y, w, h))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Split into one area per percentage of the space, leftover cells going to the last one.
+    /// Percentages are normalized by their sum.
+    pub fn percentages(self, pcts: &[u16]) -> Vec<Area> {
+        let len = self.len();
+        let space = len.saturating_sub(self.gap * pcts.len().saturating_sub(1));
+        let total: usize = pcts.iter().map(|p| *p as usize).sum();
+        let mut sizes: Vec<_> = if total == 0 {
+            even(len, pcts.len(), self.gap)
+        } else {
+            pcts.iter().map(|p| space * *p as usize / total).collect()
+        };
+        let used: usize = sizes.iter().sum();
+        if let Some(last) = sizes.last_mut() {
+            *last += space - used;
+        }
+        place(len, &sizes, self.gap)
+            .into_iter()
+            .map(|(offset, size)| self.along(offset, size))
+            .collect()
+    }
+
+    /// Length of the split axis
+    fn len(&self) -> usize {
+        if self.vertical {
+            self.area.h
+        } else {
+            self.area.w
+        }
+    }
+
+    /// Area covering `size` cells from `offset` along the split axis
+    fn along(&self, offset: usize, size: usize) -> Area {
+        if self.vertical {
+            Area {
+                y: self.area.y + offset,
+                h: size,
+                ..self.area
+            }
+        } else {
+            Area {
+                x: self.area.x + offset,
+                w: size,
+                ..self.area
+            }
+        }
+    }
+
+    /// Space to share along the split axis
+    fn space(&self) -> usize {
+        self.len().saturating_sub(self.gap)
+    }
+
+    /// Split with the first area getting `first` cells and the second the rest
+    fn cut(self, first: usize) -> (Area, Area) {
+        let second = self.space() - first;
         if self.vertical {
-            let space = self.area.h - self.gap;
-            let (first, second) = (space / 2, space / 2 + space % 2);
             (
                 Area {
                     h: first,
@@ -283,8 +1228,6 @@ impl SplitBuilder {
                 },
             )
         } else {
-            let space = self.area.w - self.gap;
-            let (first, second) = (space / 2, space / 2 + space % 2);
             (
                 Area {
                     w: first,
@@ -299,3 +1242,215 @@ impl SplitBuilder {
         }
     }
 }
+
+/// Split `len` cells in `n` sizes separated by `gap`, the first ones getting the remainder
+fn even(len: usize, n: usize, gap: usize) -> Vec<usize> {
+    let space = len.saturating_sub(gap * n.saturating_sub(1));
+    (0..n)
+        .map(|i| space / n + usize::from(i < space % n))
+        .collect()
+}
+
+/// Offsets of consecutive sizes separated by `gap`, clamped to `len`
+fn place(len: usize, sizes: &[usize], gap: usize) -> Vec<(usize, usize)> {
+    let mut pos = 0;
+    sizes
+        .iter()
+        .map(|&size| {
+            let offset = pos.min(len);
+            pos += size + gap;
+            (offset, size)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserve_columns() {
+        let mut buf = Buffer::empty(20, 5);
+        let mut c = buf.canvas();
+        let gutter = c.reserve_left(4);
+        let scrollbar = c.reserve_right(1);
+        assert_eq!(gutter, Area::new(0, 0, 4, 5));
+        assert_eq!(scrollbar, Area::new(19, 0, 1, 5));
+        assert_eq!((c.area.x, c.area.w, c.area.h), (4, 15, 5));
+
+        // Nothing left to draw on
+        let mut buf = Buffer::empty(4, 2);
+        let mut c = buf.canvas();
+        c.reserve_left(4);
+        c.fill('x', none()).line("a", none());
+        c.for_each_line(|_, mut line| {
+            line.draw("b", none());
+        });
+        let mut c = buf.canvas();
+        c.reserve_right(4);
+        c.top().draw("c", none());
+        assert_eq!(buf.snapshot(), "\n");
+    }
+
+    #[test]
+    fn sub_canvas() {
+        let mut buf = Buffer::empty(6, 2);
+        let mut c = buf.canvas();
+        let gutter = c.reserve_left(2);
+        c.sub(gutter).line("1", none()).line("2", none());
+        c.sub(Area::new(0, 1, 10, 1)).line("overflowing", none());
+        c.line("main", none());
+        assert_eq!(buf.snapshot(), "1 main\noverfl");
+    }
+
+    #[test]
+    fn truncate() {
+        assert_eq!(truncate_to_width("hello", 3), ("hel", true));
+        assert_eq!(truncate_to_width("hello", 5), ("hello", false));
+        assert_eq!(truncate_to_width("コンピ", 3), ("コ", true));
+        assert_eq!(truncate_to_width("コンピ", 0), ("", true));
+        // Combining marks stay with their base
+        assert_eq!(truncate_to_width("ae\u{301}b", 2), ("ae\u{301}", true));
+        assert_eq!(truncate_to_width("ae\u{301}", 1), ("a", true));
+    }
+
+    #[test]
+    fn char_and_grapheme_width() {
+        assert_eq!(char_width('a'), 1);
+        assert_eq!(char_width('中'), 2);
+        assert_eq!(char_width('\u{301}'), 0);
+        assert_eq!(char_width('\t'), 0);
+        assert_eq!(grapheme_width("e\u{301}"), 1);
+        assert_eq!(grapheme_width("中"), 2);
+        assert_eq!(grapheme_width("\t"), 0);
+    }
+
+    #[test]
+    fn pad() {
+        assert_eq!(pad_right("コン", 6), "コン  ");
+        assert_eq!(pad_left("コン", 6), "  コン");
+        assert_eq!(pad_right("コンピ", 4), "コン");
+        assert_eq!(pad_right("コンピ", 5), "コン ");
+        assert_eq!(pad_left("e\u{301}", 2), " e\u{301}");
+    }
+
+    #[test]
+    fn area_inner() {
+        let area = Area::new(0, 0, 10, 4);
+        assert_eq!(area.inner(1), Area::new(1, 1, 8, 2));
+        assert_eq!(area.inner(5).height(), 0);
+        assert_eq!(area.inner(5).width(), 0);
+        assert_eq!(area.padded(0, 2, 1, 3), Area::new(3, 0, 5, 3));
+    }
+
+    #[test]
+    fn area_hit_testing() {
+        let area = Area::new(2, 1, 3, 2);
+        assert!(area.contains(2, 1));
+        assert!(area.contains(4, 2));
+        assert!(!area.contains(5, 2));
+        assert!(!area.contains(4, 3));
+        assert!(!area.contains(1, 1));
+
+        let other = Area::new(4, 0, 5, 2);
+        assert_eq!(area.intersect(&other), Some(Area::new(4, 1, 1, 1)));
+        assert_eq!(area.intersect(&Area::new(5, 1, 2, 2)), None);
+        assert_eq!(area.intersect(&Area::new(0, 3, 10, 2)), None);
+    }
+
+    #[test]
+    fn split_grid() {
+        let mut buf = Buffer::empty(10, 6);
+        let grid = buf.canvas().split().grid(2, 2);
+        assert_eq!(
+            grid,
+            vec![
+                vec![Area::new(0, 0, 5, 3), Area::new(5, 0, 5, 3)],
+                vec![Area::new(0, 3, 5, 3), Area::new(5, 3, 5, 3)],
+            ]
+        );
+
+        // Remainders and gaps
+        let grid = buf.canvas().split().gap(1).grid(1, 3);
+        assert_eq!(
+            grid,
+            vec![vec![
+                Area::new(0, 0, 3, 6),
+                Area::new(4, 0, 3, 6),
+                Area::new(8, 0, 2, 6)
+            ]]
+        );
+
+        // Gaps larger than the area
+        let grid = buf.canvas().split().gap(4).grid(3, 1);
+        assert!(grid.iter().flatten().all(|a| a.height() == 0));
+        assert!(buf.canvas().split().grid(0, 2).is_empty());
+    }
+
+    #[test]
+    fn split_percentages() {
+        let mut buf = Buffer::empty(100, 10);
+        let widths: Vec<_> = (buf.canvas().split().percentages(&[20, 60, 20]))
+            .iter()
+            .map(|a| (a.x(), a.width()))
+            .collect();
+        assert_eq!(widths, vec![(0, 20), (20, 60), (80, 20)]);
+
+        // Leftovers land in the last area
+        let heights: Vec<_> = (buf.canvas().split().vertical(true).gap(1))
+            .percentages(&[33, 33, 34])
+            .iter()
+            .map(|a| (a.y(), a.height()))
+            .collect();
+        assert_eq!(heights, vec![(0, 2), (3, 2), (6, 4)]);
+    }
+
+    #[test]
+    fn split_ratio() {
+        let mut buf = Buffer::empty(30, 5);
+        let (first, second) = buf.canvas().split().ratio(1, 2);
+        assert_eq!(first, Area::new(0, 0, 10, 5));
+        assert_eq!(second, Area::new(10, 0, 20, 5));
+
+        let mut buf = Buffer::empty(11, 7);
+        let mut c = buf.canvas();
+        let area = c.reserve_top(7);
+        for vertical in [false, true] {
+            let ratio = c
+                .consume(area)
+                .split()
+                .vertical(vertical)
+                .gap(1)
+                .ratio(1, 1);
+            let apply = c.consume(area).split().vertical(vertical).gap(1).apply();
+            assert_eq!(ratio, apply);
+        }
+    }
+
+    #[test]
+    fn split_fixed() {
+        let mut buf = Buffer::empty(10, 10);
+        let mut c = buf.canvas();
+        let area = c.reserve_top(10);
+
+        let (header, body) = c.consume(area).split().vertical(true).fixed_first(3);
+        assert_eq!(header, Area::new(0, 0, 10, 3));
+        assert_eq!(body, Area::new(0, 3, 10, 7));
+
+        let (body, footer) = c
+            .consume(area)
+            .split()
+            .vertical(true)
+            .gap(1)
+            .fixed_second(2);
+        assert_eq!(body, Area::new(0, 0, 10, 7));
+        assert_eq!(footer, Area::new(0, 8, 10, 2));
+
+        // Clamp to the available space
+        let (fixed, flex) = c.consume(area).split().gap(2).fixed_first(9);
+        assert_eq!((fixed.w, flex.w), (8, 0));
+        let (flex, fixed) = c.consume(area).split().gap(2).fixed_second(12);
+        assert_eq!((flex.w, fixed.w), (0, 8));
+        assert_eq!(fixed.x, 2);
+    }
+}
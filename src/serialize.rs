@@ -0,0 +1,185 @@
+use std::fmt;
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::{
+    color::hex,
+    style::{Color, Modifier, Style},
+};
+
+const COLORS: [(&str, Color); 17] = [
+    ("reset", Color::Reset),
+    ("black", Color::Black),
+    ("dark_grey", Color::DarkGrey),
+    ("red", Color::Red),
+    ("dark_red", Color::DarkRed),
+    ("green", Color::Green),
+    ("dark_green", Color::DarkGreen),
+    ("yellow", Color::Yellow),
+    ("dark_yellow", Color::DarkYellow),
+    ("blue", Color::Blue),
+    ("dark_blue", Color::DarkBlue),
+    ("magenta", Color::Magenta),
+    ("dark_magenta", Color::DarkMagenta),
+    ("cyan", Color::Cyan),
+    ("dark_cyan", Color::DarkCyan),
+    ("white", Color::White),
+    ("grey", Color::Grey),
+];
+
+const MODIFIERS: [(&str, Modifier); 9] = [
+    ("bold", Modifier::BOLD),
+    ("dim", Modifier::DIM),
+    ("italic", Modifier::ITALIC),
+    ("underline", Modifier::UNDERLINED),
+    ("reversed", Modifier::REVERSED),
+    ("crossed_out", Modifier::CROSSED_OUT),
+    ("blink", Modifier::BLINK),
+    ("rapid_blink", Modifier::RAPID_BLINK),
+    ("hidden", Modifier::HIDDEN),
+];
+
+/// Color as its name, `#rrggbb` or palette index
+struct ColorDef(Color);
+
+impl Serialize for ColorDef {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Color::Rgb { r, g, b } => serializer.serialize_str(&format!("#{r:02x}{g:02x}{b:02x}")),
+            Color::AnsiValue(n) => serializer.serialize_u8(n),
+            color => {
+                let (name, _) = COLORS.iter().find(|(_, c)| *c == color).unwrap();
+                serializer.serialize_str(name)
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ColorDef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ColorVisitor;
+
+        impl Visitor<'_> for ColorVisitor {
+            type Value = ColorDef;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a color name, an hex color or a palette index")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<ColorDef, E> {
+                u8::try_from(v)
+                    .map(|n| ColorDef(Color::AnsiValue(n)))
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<ColorDef, E> {
+                if let Some((_, color)) = COLORS.iter().find(|(name, _)| *name == v) {
+                    Ok(ColorDef(*color))
+                } else if v.starts_with('#') {
+                    hex(v).map(ColorDef).map_err(E::custom)
+                } else {
+                    Err(E::invalid_value(de::Unexpected::Str(v), &self))
+                }
+            }
+        }
+
+        deserializer.deserialize_any(ColorVisitor)
+    }
+}
+
+/// Modifier as a list of names
+impl Serialize for Modifier {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            MODIFIERS
+                .iter()
+                .filter(|(_, flag)| self.contains(*flag))
+                .map(|(name, _)| name),
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for Modifier {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut modifier = Modifier::empty();
+        for name in Vec::<String>::deserialize(deserializer)? {
+            let (_, flag) = MODIFIERS
+                .iter()
+                .find(|(n, _)| *n == name)
+                .ok_or_else(|| de::Error::custom(format!("unknown modifier {name:?}")))?;
+            modifier |= *flag;
+        }
+        Ok(modifier)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Style")]
+struct StyleDef {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fg: Option<ColorDef>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bg: Option<ColorDef>,
+    #[serde(
+        default = "Modifier::empty",
+        skip_serializing_if = "Modifier::is_empty"
+    )]
+    modifiers: Modifier,
+}
+
+impl Serialize for Style {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        StyleDef {
+            fg: self.fg.map(ColorDef),
+            bg: self.bg.map(ColorDef),
+            modifiers: self.modifier,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Style {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let def = StyleDef::deserialize(deserializer)?;
+        Ok(Style {
+            fg: def.fg.map(|c| c.0),
+            bg: def.bg.map(|c| c.0),
+            modifier: def.modifiers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::style::{none, rgb};
+
+    use super::*;
+
+    #[test]
+    fn style_round_trip() {
+        let style = none().fg(Color::DarkRed).bg(rgb(255, 136, 0)).bold();
+        let json = serde_json::to_string(&style).unwrap();
+        assert_eq!(
+            json,
+            r##"{"fg":"dark_red","bg":"#ff8800","modifiers":["bold"]}"##
+        );
+        assert_eq!(serde_json::from_str::<Style>(&json).unwrap(), style);
+
+        let style = none().fg(Color::AnsiValue(196)).underline().italic();
+        let json = serde_json::to_string(&style).unwrap();
+        assert_eq!(json, r#"{"fg":196,"modifiers":["italic","underline"]}"#);
+        assert_eq!(serde_json::from_str::<Style>(&json).unwrap(), style);
+
+        assert_eq!(serde_json::from_str::<Style>("{}").unwrap(), none());
+        assert!(serde_json::from_str::<Style>(r#"{"fg":"purple"}"#).is_err());
+        assert!(serde_json::from_str::<Style>(r#"{"modifiers":["loud"]}"#).is_err());
+
+        let modifier = Modifier::BOLD | Modifier::HIDDEN;
+        let json = serde_json::to_string(&modifier).unwrap();
+        assert_eq!(json, r#"["bold","hidden"]"#);
+        assert_eq!(serde_json::from_str::<Modifier>(&json).unwrap(), modifier);
+    }
+}
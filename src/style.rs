@@ -1,43 +1,118 @@
-use std::io::{self, Stdout};
+use std::io;
 
 use bitflags::bitflags;
-use crossterm::{queue, style::SetAttribute};
 
-pub use crossterm::style::{Attribute, Color};
+use super::backend::Backend;
+
+/// Terminal color, palette colors depending on the terminal theme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Color {
+    /// Terminal default foreground or background
+    Reset,
+    Black,
+    DarkGrey,
+    Red,
+    DarkRed,
+    Green,
+    DarkGreen,
+    Yellow,
+    DarkYellow,
+    Blue,
+    DarkBlue,
+    Magenta,
+    DarkMagenta,
+    Cyan,
+    DarkCyan,
+    White,
+    Grey,
+    Rgb {
+        r: u8,
+        g: u8,
+        b: u8,
+    },
+    /// Color of the 256 colors palette
+    AnsiValue(u8),
+}
+
+/// Text attribute set or unset by a backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Attribute {
+    /// Unset every attribute and color
+    Reset,
+    Bold,
+    Dim,
+    Italic,
+    Underlined,
+    SlowBlink,
+    RapidBlink,
+    Reverse,
+    Hidden,
+    CrossedOut,
+    /// Neither bold nor dim
+    NormalIntensity,
+    NoItalic,
+    NoUnderline,
+    NoBlink,
+    NoReverse,
+    NoHidden,
+    NotCrossedOut,
+}
 
 bitflags! {
+    /// Set of text emphasis
     #[derive(Clone, Debug, Copy, PartialEq, Eq)]
-    pub(crate) struct Modifier: u8 {
-        const BOLD              = 0b0000_0001;
-        const DIM               = 0b0000_0010;
-        const ITALIC            = 0b0000_0100;
-        const UNDERLINED        = 0b0000_1000;
-        const REVERSED          = 0b0001_0000;
-        const CROSSED_OUT       = 0b0010_0000;
+    pub struct Modifier: u16 {
+        const BOLD              = 0b0000_0000_0001;
+        const DIM               = 0b0000_0000_0010;
+        const ITALIC            = 0b0000_0000_0100;
+        const UNDERLINED        = 0b0000_0000_1000;
+        const REVERSED          = 0b0000_0001_0000;
+        const CROSSED_OUT       = 0b0000_0010_0000;
+        const BLINK             = 0b0000_0100_0000;
+        const RAPID_BLINK       = 0b0000_1000_0000;
+        const HIDDEN            = 0b0001_0000_0000;
     }
 }
 
 impl Modifier {
-    pub fn diff(w: &mut Stdout, from: Modifier, to: Modifier) -> io::Result<()> {
-        for removed in (from - to).iter() {
+    /// Queue the attributes changing the emphasis from `from` to `to`
+    pub(crate) fn diff(w: &mut impl Backend, from: Modifier, to: Modifier) -> io::Result<()> {
+        let removed = from - to;
+        let mut added = to - from;
+        // Some flags share their reset attribute, the remaining one must be applied again
+        for shared in [
+            Modifier::BOLD | Modifier::DIM,
+            Modifier::BLINK | Modifier::RAPID_BLINK,
+        ] {
+            if removed.intersects(shared) {
+                added |= to & shared;
+            }
+        }
+        for removed in removed.iter() {
             match removed {
-                Modifier::REVERSED => queue!(w, SetAttribute(Attribute::NoReverse))?,
-                Modifier::BOLD => queue!(w, SetAttribute(Attribute::NormalIntensity))?,
-                Modifier::ITALIC => queue!(w, SetAttribute(Attribute::NoItalic))?,
-                Modifier::UNDERLINED => queue!(w, SetAttribute(Attribute::NoUnderline))?,
-                Modifier::DIM => queue!(w, SetAttribute(Attribute::NormalIntensity))?,
-                Modifier::CROSSED_OUT => queue!(w, SetAttribute(Attribute::NotCrossedOut))?,
+                Modifier::REVERSED => w.set_attribute(Attribute::NoReverse)?,
+                Modifier::BOLD => w.set_attribute(Attribute::NormalIntensity)?,
+                Modifier::ITALIC => w.set_attribute(Attribute::NoItalic)?,
+                Modifier::UNDERLINED => w.set_attribute(Attribute::NoUnderline)?,
+                Modifier::DIM => w.set_attribute(Attribute::NormalIntensity)?,
+                Modifier::CROSSED_OUT => w.set_attribute(Attribute::NotCrossedOut)?,
+                Modifier::BLINK => w.set_attribute(Attribute::NoBlink)?,
+                Modifier::RAPID_BLINK => w.set_attribute(Attribute::NoBlink)?,
+                Modifier::HIDDEN => w.set_attribute(Attribute::NoHidden)?,
                 _ => unreachable!("Unknown modifier flag"),
             }
         }
-        for added in (to - from).iter() {
+        for added in added.iter() {
             match added {
-                Modifier::REVERSED => queue!(w, SetAttribute(Attribute::Reverse))?,
-                Modifier::BOLD => queue!(w, SetAttribute(Attribute::Bold))?,
-                Modifier::ITALIC => queue!(w, SetAttribute(Attribute::Italic))?,
-                Modifier::UNDERLINED => queue!(w, SetAttribute(Attribute::Underlined))?,
-                Modifier::DIM => queue!(w, SetAttribute(Attribute::Dim))?,
-                Modifier::CROSSED_OUT => queue!(w, SetAttribute(Attribute::CrossedOut))?,
+                Modifier::REVERSED => w.set_attribute(Attribute::Reverse)?,
+                Modifier::BOLD => w.set_attribute(Attribute::Bold)?,
+                Modifier::ITALIC => w.set_attribute(Attribute::Italic)?,
+                Modifier::UNDERLINED => w.set_attribute(Attribute::Underlined)?,
+                Modifier::DIM => w.set_attribute(Attribute::Dim)?,
+                Modifier::CROSSED_OUT => w.set_attribute(Attribute::CrossedOut)?,
+                Modifier::BLINK => w.set_attribute(Attribute::SlowBlink)?,
+                Modifier::RAPID_BLINK => w.set_attribute(Attribute::RapidBlink)?,
+                Modifier::HIDDEN => w.set_attribute(Attribute::Hidden)?,
                 _ => unreachable!("Unknown modifier flag"),
             }
         }
@@ -60,6 +135,11 @@ pub const fn none() -> Style {
     }
 }
 
+/// Truecolor from its red, green and blue components
+pub const fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color::Rgb { r, g, b }
+}
+
 impl Default for Style {
     fn default() -> Style {
         none()
@@ -79,43 +159,204 @@ impl Style {
         self
     }
 
-    pub fn bold(self) -> Style {
+    /// Resets the foreground to the terminal default, unlike an unset color which is left
+    /// unchanged
+    pub const fn reset_fg(self) -> Style {
+        self.fg(Color::Reset)
+    }
+
+    /// Resets the background to the terminal default, unlike an unset color which is left
+    /// unchanged
+    pub const fn reset_bg(self) -> Style {
+        self.bg(Color::Reset)
+    }
+
+    /// Changes the foreground to a truecolor
+    ///
+    /// ```
+    /// use tui::{none, Terminal};
+    ///
+    /// // Gradient bar from blue to red
+    /// let mut term = Terminal::test(8, 1);
+    /// term.draw(|c| {
+    ///     let mut line = c.top();
+    ///     for i in 0..8u8 {
+    ///         line.draw('█', none().rgb_fg(i * 32, 0, 255 - i * 32));
+    ///     }
+    /// })
+    /// .unwrap();
+    /// ```
+    pub const fn rgb_fg(self, r: u8, g: u8, b: u8) -> Style {
+        self.fg(rgb(r, g, b))
+    }
+
+    /// Changes the background to a truecolor
+    pub const fn rgb_bg(self, r: u8, g: u8, b: u8) -> Style {
+        self.bg(rgb(r, g, b))
+    }
+
+    /// Apply another style on top, taking its colors when set and adding its modifiers
+    pub fn patch(self, other: Style) -> Style {
+        Style {
+            fg: other.fg.or(self.fg),
+            bg: other.bg.or(self.bg),
+            modifier: self.modifier | other.modifier,
+        }
+    }
+
+    /// Swap the foreground and background colors, toggling reversed video when both are unset
+    pub fn invert(mut self) -> Style {
+        if self.fg.is_none() && self.bg.is_none() {
+            self.modifier.toggle(Modifier::REVERSED);
+        } else {
+            std::mem::swap(&mut self.fg, &mut self.bg);
+        }
+        self
+    }
+
+    /// Text emphasis
+    pub const fn modifier(&self) -> Modifier {
+        self.modifier
+    }
+
+    /// Replaces the text emphasis
+    pub const fn with_modifier(mut self, modifier: Modifier) -> Style {
+        self.modifier = modifier;
+        self
+    }
+
+    pub const fn bold(self) -> Style {
         self.add_modifier(Modifier::BOLD)
     }
 
-    pub fn dim(self) -> Style {
+    pub const fn dim(self) -> Style {
         self.add_modifier(Modifier::DIM)
     }
 
-    pub fn italic(self) -> Style {
+    pub const fn italic(self) -> Style {
         self.add_modifier(Modifier::ITALIC)
     }
 
-    pub fn underline(self) -> Style {
+    pub const fn underline(self) -> Style {
         self.add_modifier(Modifier::UNDERLINED)
     }
 
-    pub fn reversed(self) -> Style {
+    pub const fn reversed(self) -> Style {
         self.add_modifier(Modifier::REVERSED)
     }
 
-    pub fn croosed_out(self) -> Style {
+    pub const fn crossed_out(self) -> Style {
         self.add_modifier(Modifier::CROSSED_OUT)
     }
 
-    pub fn clear_emphasis(self) -> Style {
+    #[deprecated(note = "use `crossed_out` instead")]
+    pub const fn croosed_out(self) -> Style {
+        self.crossed_out()
+    }
+
+    pub const fn blink(self) -> Style {
+        self.add_modifier(Modifier::BLINK)
+    }
+
+    pub const fn rapid_blink(self) -> Style {
+        self.add_modifier(Modifier::RAPID_BLINK)
+    }
+
+    pub const fn hidden(self) -> Style {
+        self.add_modifier(Modifier::HIDDEN)
+    }
+
+    pub const fn clear_emphasis(self) -> Style {
         self.remove_modifier(Modifier::all())
     }
 
     /// Changes the text emphasis
-    fn add_modifier(mut self, modifier: Modifier) -> Style {
-        self.modifier.insert(modifier);
+    const fn add_modifier(mut self, modifier: Modifier) -> Style {
+        self.modifier = self.modifier.union(modifier);
         self
     }
 
     /// Changes the text emphasis
-    fn remove_modifier(mut self, modifier: Modifier) -> Style {
-        self.modifier.remove(modifier);
+    const fn remove_modifier(mut self, modifier: Modifier) -> Style {
+        self.modifier = self.modifier.difference(modifier);
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn const_builders() {
+        const HEADER: Style = none().fg(Color::Cyan).bold().underline();
+        assert_eq!(HEADER.fg, Some(Color::Cyan));
+        assert_eq!(HEADER.modifier, Modifier::BOLD | Modifier::UNDERLINED);
+        const PLAIN: Style = HEADER.clear_emphasis();
+        assert_eq!(PLAIN, none().fg(Color::Cyan));
+    }
+
+    #[test]
+    fn modifier_set() {
+        let style = none().bold().italic();
+        assert_eq!(style.modifier(), Modifier::BOLD | Modifier::ITALIC);
+        assert_eq!(style.with_modifier(Modifier::DIM), none().dim(),);
+        assert_eq!(none().with_modifier(style.modifier()), style);
+    }
+
+    #[test]
+    fn patch() {
+        let style = none().fg(Color::Red).bg(Color::Blue);
+        assert_eq!(
+            style.patch(none().bold()),
+            none().fg(Color::Red).bg(Color::Blue).bold()
+        );
+        assert_eq!(
+            style.italic().patch(none().fg(Color::Green).bold()),
+            none().fg(Color::Green).bg(Color::Blue).italic().bold()
+        );
+    }
+
+    #[test]
+    fn reset_colors() {
+        let themed = none().fg(Color::Red).bg(Color::Blue);
+        assert_eq!(themed.patch(none()), themed);
+        let patched = themed.patch(none().reset_fg());
+        assert_eq!(patched.fg, Some(Color::Reset));
+        assert_eq!(patched.bg, Some(Color::Blue));
+        assert_eq!(themed.patch(none().reset_bg()).bg, Some(Color::Reset));
+    }
+
+    #[test]
+    fn invert() {
+        let style = none().fg(Color::White).bg(Color::Blue).bold();
+        assert_eq!(
+            style.invert(),
+            none().fg(Color::Blue).bg(Color::White).bold()
+        );
+        assert_eq!(none().fg(Color::Red).invert(), none().bg(Color::Red));
+        assert_eq!(none().invert(), none().reversed());
+        assert_eq!(none().reversed().invert(), none());
+    }
+
+    #[cfg(feature = "crossterm")]
+    fn diff(from: Style, to: Style) -> String {
+        let mut out = crate::backend::CrosstermBackend::new(Vec::new());
+        Modifier::diff(&mut out, from.modifier, to.modifier).unwrap();
+        out.flush().unwrap();
+        String::from_utf8(std::mem::take(out.writer())).unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn modifier_diff() {
+        assert_eq!(diff(none(), none().blink()), "\x1b[5m");
+        assert_eq!(diff(none().hidden(), none()), "\x1b[28m");
+        // Shared reset must re-apply the remaining flag
+        assert_eq!(
+            diff(none().blink().rapid_blink(), none().rapid_blink()),
+            "\x1b[25m\x1b[6m"
+        );
+        assert_eq!(diff(none().bold().dim(), none().dim()), "\x1b[22m\x1b[2m");
+    }
+}
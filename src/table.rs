@@ -0,0 +1,197 @@
+use super::{style::Style, width, Area, Canvas};
+
+/// Horizontal placement of a text in a table cell
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Width and alignment of a table column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Column {
+    /// Exact width, None for flex columns
+    fixed: Option<usize>,
+    flex: u32,
+    align: Align,
+}
+
+impl Column {
+    /// Column of exactly `width` cells
+    pub fn fixed(width: usize) -> Self {
+        Column {
+            fixed: Some(width),
+            flex: 0,
+            align: Align::Left,
+        }
+    }
+
+    /// Column sharing the space left by fixed columns with the other flex ones by `ratio`
+    pub fn flex(ratio: u32) -> Self {
+        Column {
+            fixed: None,
+            flex: ratio,
+            align: Align::Left,
+        }
+    }
+
+    /// Change the alignment, left by default
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+}
+
+/// Rows of texts drawn in aligned columns, overflowing texts ending with an ellipsis
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    columns: Vec<Column>,
+    header: Option<(Vec<String>, Style)>,
+    rows: Vec<Vec<String>>,
+    separator: Option<char>,
+}
+
+impl Table {
+    pub fn new(columns: Vec<Column>) -> Self {
+        Table {
+            columns,
+            header: None,
+            rows: Vec::new(),
+            separator: None,
+        }
+    }
+
+    /// Add a first row drawn with its own style
+    pub fn header(mut self, cells: Vec<String>, style: Style) -> Self {
+        self.header = Some((cells, style));
+        self
+    }
+
+    pub fn rows(mut self, rows: Vec<Vec<String>>) -> Self {
+        self.rows = rows;
+        self
+    }
+
+    /// Draw a char between columns instead of a blank cell
+    pub fn separator(mut self, c: char) -> Self {
+        self.separator = Some(c);
+        self
+    }
+
+    /// Width of each column in `len` cells, columns being one cell apart. Fixed columns are
+    /// served first in order, the flex ones sharing the rest with the remainder going to the last.
+    pub fn widths(&self, len: usize) -> Vec<usize> {
+        let mut space = len.saturating_sub(self.columns.len().saturating_sub(1));
+        let mut widths: Vec<_> = self
+            .columns
+            .iter()
+            .map(|col| {
+                let width = col.fixed.unwrap_or(0).min(space);
+                space -= width;
+                width
+            })
+            .collect();
+        let total: u64 = self.columns.iter().map(|col| col.flex as u64).sum();
+        if total == 0 {
+            return widths;
+        }
+        let mut last = None;
+        let mut used = 0;
+        for (i, col) in self.columns.iter().enumerate() {
+            if col.fixed.is_none() && col.flex > 0 {
+                widths[i] = (space as u64 * col.flex as u64 / total) as usize;
+                used += widths[i];
+                last = Some(i);
+            }
+        }
+        if let Some(last) = last {
+            widths[last] += space - used;
+        }
+        widths
+    }
+
+    /// Draw the header and the rows fitting in the canvas, one per line from the top
+    pub fn render(&self, c: &mut Canvas, style: Style) {
+        let widths = self.widths(c.area.w);
+        let header = self.header.iter().map(|(cells, style)| (cells, *style));
+        for (cells, style) in header.chain(self.rows.iter().map(|row| (row, style))) {
+            if c.area.h == 0 {
+                break;
+            }
+            let row = c.area.top(1);
+            let mut x = row.x;
+            for (i, (col, &w)) in self.columns.iter().zip(&widths).enumerate() {
+                if i > 0 {
+                    if let Some(sep) = self.separator {
+                        c.sub(Area::new(x, row.y, 1, 1)).top().draw(sep, style);
+                    }
+                    x += 1;
+                }
+                let text = cells.get(i).map_or("", String::as_str);
+                let mut cell = c.sub(Area::new(x, row.y, w, 1));
+                let mut line = cell.top();
+                match col.align {
+                    _ if width(text) > w => line.draw_truncated(text, style, "…"),
+                    Align::Left => line.draw(text, style),
+                    Align::Center => line.cdraw(text, style),
+                    Align::Right => line.rdraw(text, style),
+                };
+                x += w;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        buffer::Buffer,
+        style::{none, Color},
+    };
+
+    use super::*;
+
+    #[test]
+    fn column_widths() {
+        let table = Table::new(vec![
+            Column::fixed(4),
+            Column::flex(1),
+            Column::fixed(3),
+            Column::flex(2),
+        ]);
+        // 3 gaps, 7 fixed, 10 flex
+        assert_eq!(table.widths(20), [4, 3, 3, 7]);
+        assert_eq!(table.widths(10), [4, 0, 3, 0]);
+        assert_eq!(table.widths(5), [2, 0, 0, 0]);
+
+        let table = Table::new(vec![Column::flex(1), Column::flex(1), Column::flex(1)]);
+        assert_eq!(table.widths(12), [3, 3, 4]);
+    }
+
+    #[test]
+    fn render() {
+        let mut buf = Buffer::empty(14, 4);
+        let rows = vec![
+            vec!["a".to_string(), "1".to_string(), "x".to_string()],
+            vec!["long name".to_string(), "22".to_string(), "yy".to_string()],
+            vec!["overflow".to_string()],
+        ];
+        Table::new(vec![
+            Column::flex(1),
+            Column::fixed(3).align(Align::Right),
+            Column::fixed(4).align(Align::Center),
+        ])
+        .header(vec!["Name".into(), "N".into()], none().bold())
+        .rows(rows)
+        .separator('│')
+        .render(&mut buf.canvas(), none());
+        assert_eq!(
+            buf.snapshot(),
+            "Name │  N│\na    │  1│ x\nlong…│ 22│ yy\nover…│   │"
+        );
+        let header = none().bold().fg(Color::Reset).bg(Color::Reset);
+        assert_eq!(buf.content[0].style(), header);
+    }
+}
@@ -1,157 +1,1219 @@
-use std::io::{self, Write};
-
-use crossterm::{
-    cursor::{Hide, MoveTo, Show},
-    execute, queue,
-    style::{
-        Attribute, Colors, Print, SetAttribute, SetBackgroundColor, SetColors, SetForegroundColor,
-    },
-    terminal::{
-        self, disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
-        LeaveAlternateScreen,
-    },
-};
+use std::io;
 
+#[cfg(feature = "crossterm")]
+use super::backend::CrosstermBackend;
 use super::{
-    buffer::{Buffer, Cell},
-    style::{Color, Modifier},
+    backend::{Backend, ClearType, HeadlessBackend, KeyboardEnhancementFlags},
+    buffer::{Buffer, Cell, Change},
+    color::ColorDepth,
+    frame::Frame,
+    style::{Attribute, Color, Modifier, Style},
     Canvas,
 };
 
-pub struct Terminal {
-    out: io::Stdout,
+/// Shape of the terminal cursor
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CursorStyle {
+    /// Shape configured by the user
+    #[default]
+    Default,
+    BlinkingBlock,
+    SteadyBlock,
+    BlinkingUnderline,
+    SteadyUnderline,
+    BlinkingBar,
+    SteadyBar,
+}
+
+/// How the terminal is driven
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Full screen in the alternate screen
+    Fullscreen,
+    /// Viewport starting at a row of the main screen
+    Inline { origin: u16 },
+    /// No real terminal behind the backend
+    Headless,
+}
+
+impl Mode {
+    /// First row of the drawing area
+    fn origin(&self) -> u16 {
+        match self {
+            Mode::Inline { origin } => *origin,
+            _ => 0,
+        }
+    }
+}
+
+/// Attributes currently applied by the terminal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Pen {
+    colors: (Color, Color),
+    modifier: Modifier,
+    depth: ColorDepth,
+    monochrome: bool,
+}
+
+impl Pen {
+    /// Attributes after a reset, colors being downsampled to `depth`
+    pub fn new(depth: ColorDepth) -> Pen {
+        Pen {
+            colors: (Color::Reset, Color::Reset),
+            modifier: Modifier::empty(),
+            depth,
+            monochrome: false,
+        }
+    }
+
+    /// Never emit colors
+    pub fn monochrome(mut self, monochrome: bool) -> Pen {
+        self.monochrome = monochrome;
+        self
+    }
+
+    /// Colors of a cell as emitted
+    fn colors(&self, cell: &Cell) -> (Color, Color) {
+        if self.monochrome {
+            (Color::Reset, Color::Reset)
+        } else {
+            (self.depth.map(cell.fg), self.depth.map(cell.bg))
+        }
+    }
+
+    /// Whether `cell` is drawn without changing attributes
+    pub fn matches(&self, cell: &Cell) -> bool {
+        self.modifier == cell.modifier && self.colors == self.colors(cell)
+    }
+
+    /// Queue the attribute changes needed to draw `cell`, modifiers first then colors at once
+    /// so that no cell is ever shown with one without the other
+    pub fn apply(&mut self, out: &mut impl Backend, cell: &Cell) -> io::Result<()> {
+        if cell.modifier != self.modifier {
+            Modifier::diff(out, self.modifier, cell.modifier)?;
+            self.modifier = cell.modifier;
+        }
+        let new = self.colors(cell);
+        match (self.colors.0 == new.0, self.colors.1 == new.1) {
+            (false, false) => out.set_colors(new.0, new.1)?,
+            (false, true) => out.set_fg(new.0)?,
+            (true, false) => out.set_bg(new.1)?,
+            (true, true) => {}
+        }
+        self.colors = new;
+        Ok(())
+    }
+}
+
+pub struct Terminal<
+    #[cfg(feature = "crossterm")] B: Backend = CrosstermBackend<io::Stdout>,
+    #[cfg(not(feature = "crossterm"))] B: Backend,
+> {
+    /// Output is queued and flushed once per frame
+    backend: B,
     draw_buffer: Buffer,
+    /// Last frame drawn
     prev_buffer: Buffer,
+    /// Whether the next frame is painted in full, the screen no longer matching the last one
+    redraw: bool,
     size: (usize, usize),
+    mode: Mode,
+    mouse: bool,
+    bracketed_paste: bool,
+    /// Keyboard enhancement flags pushed and not popped yet, from the first push
+    keyboard_enhancements: Vec<KeyboardEnhancementFlags>,
+    synchronized: bool,
+    cursor_style: CursorStyle,
+    /// Cursor visibility last emitted, None when unknown
+    cursor_visible: Option<bool>,
+    color_depth: ColorDepth,
+    monochrome: bool,
+    /// Frames drawn since recording started
+    recording: Option<Vec<Frame>>,
 }
 
-impl Drop for Terminal {
+impl<B: Backend> Drop for Terminal<B> {
     fn drop(&mut self) {
-        execute!(self.out, LeaveAlternateScreen).unwrap();
-        disable_raw_mode().unwrap();
+        // Errors are ignored as we might already be unwinding from a panic
+        let out = &mut self.backend;
+        if self.mouse {
+            out.disable_mouse().ok();
+        }
+        if self.bracketed_paste {
+            out.disable_bracketed_paste().ok();
+        }
+        for _ in &self.keyboard_enhancements {
+            out.pop_keyboard_enhancement().ok();
+        }
+        if self.cursor_style != CursorStyle::Default {
+            out.set_cursor_style(CursorStyle::Default).ok();
+        }
+        match self.mode {
+            Mode::Fullscreen => {
+                out.show_cursor().ok();
+                out.leave_alternate_screen().ok();
+            }
+            Mode::Inline { origin } => {
+                // Leave the cursor below the viewport
+                let last = origin + self.size.1.saturating_sub(1) as u16;
+                out.show_cursor().ok();
+                out.move_to(0, last).ok();
+                out.print("\r\n").ok();
+            }
+            Mode::Headless => {}
+        }
+        out.flush().ok();
+        if self.mode != Mode::Headless {
+            out.disable_raw_mode().ok();
+        }
     }
 }
 
+/// Whether colors are disabled following the https://no-color.org convention
+fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+/// Restore the terminal before printing panic info
+#[cfg(feature = "crossterm")]
+fn set_panic_hook(alternate_screen: bool) {
+    let prev = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let mut out = CrosstermBackend::new(io::stdout());
+        if alternate_screen {
+            out.leave_alternate_screen().ok();
+            out.flush().ok();
+        }
+        out.disable_raw_mode().ok();
+        prev(info);
+        loop {
+            std::thread::park();
+        }
+    }));
+}
+
+#[cfg(feature = "crossterm")]
 impl Terminal {
+    /// Terminal drawing to the standard output
+    pub fn stdout() -> io::Result<Terminal> {
+        Terminal::new(io::stdout())
+    }
+}
+
+impl Terminal<HeadlessBackend> {
+    /// Headless terminal of a fixed size discarding its output, useful for testing. Use
+    /// `headless` to capture the escape sequences.
+    pub fn test(nb_col: usize, nb_row: usize) -> Self {
+        let backend = HeadlessBackend::new(nb_col as u16, nb_row as u16);
+        Terminal::headless_with_backend(backend, nb_col, nb_row)
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl<W: io::Write> Terminal<CrosstermBackend<W>> {
     /// Wrapper around Terminal initialization. Each buffer is initialized with a blank string and
     /// default colors for the foreground and the background
-    pub fn new(mut stdout: io::Stdout) -> io::Result<Terminal> {
-        let prev = std::panic::take_hook();
-        std::panic::set_hook(Box::new(move |info| {
-            execute!(io::stdout(), LeaveAlternateScreen).ok();
-            disable_raw_mode().ok();
-            prev(info);
-            loop {}
-        }));
-        enable_raw_mode()?;
-        execute!(stdout, EnterAlternateScreen)?;
-        Ok(Terminal {
-            out: stdout,
-            draw_buffer: Buffer::empty(0, 0),
-            prev_buffer: Buffer::empty(0, 0),
-            size: (0, 0),
-        })
+    pub fn new(out: W) -> io::Result<Self> {
+        set_panic_hook(true);
+        Terminal::with_backend(CrosstermBackend::new(out))
+    }
+
+    /// Inline terminal drawing into a `nb_row` tall viewport starting at the cursor row, see
+    /// `inline_with_backend`
+    pub fn inline(out: W, nb_row: usize) -> io::Result<Self> {
+        set_panic_hook(false);
+        Terminal::inline_with_backend(CrosstermBackend::new(out), nb_row)
+    }
+
+    /// Headless terminal of a fixed size that renders to `out` without touching the real
+    /// terminal mode
+    pub fn headless(out: W, nb_col: usize, nb_row: usize) -> Self {
+        Terminal::headless_with_backend(CrosstermBackend::new(out), nb_col, nb_row)
+    }
+
+    /// Underlying writer, output queued since the last flush is not written to it yet
+    pub fn writer(&mut self) -> &mut W {
+        self.backend.writer()
+    }
+}
+
+impl<B: Backend> Terminal<B> {
+    /// Full screen terminal drawing through `backend` in the alternate screen
+    pub fn with_backend(mut backend: B) -> io::Result<Self> {
+        backend.enable_raw_mode()?;
+        backend.enter_alternate_screen()?;
+        backend.flush()?;
+        Ok(Terminal::init(
+            backend,
+            (0, 0),
+            Mode::Fullscreen,
+            no_color(),
+        ))
+    }
+
+    /// Inline terminal drawing into a `nb_row` tall viewport starting at the cursor row, without
+    /// entering the alternate screen so that the output stays in the scrollback on exit.
+    ///
+    /// The content is scrolled up if the viewport does not fit below the cursor. On resize only
+    /// the width follows the terminal: the viewport keeps its height and starting row and is
+    /// cleared for a full redraw.
+    pub fn inline_with_backend(mut backend: B, nb_row: usize) -> io::Result<Self> {
+        backend.enable_raw_mode()?;
+        let (w, h) = backend.size()?;
+        let (_, y) = backend.cursor_position()?;
+        let nb_row = (nb_row as u16).min(h);
+        // Make room for the viewport
+        let overflow = (y + nb_row).saturating_sub(h);
+        if overflow > 0 {
+            backend.move_to(0, h - 1)?;
+            for _ in 0..overflow {
+                backend.print("\n")?;
+            }
+        }
+        let size = (w as usize, nb_row as usize);
+        let mode = Mode::Inline {
+            origin: y - overflow,
+        };
+        Ok(Terminal::init(backend, size, mode, no_color()))
+    }
+
+    /// Headless terminal of a fixed size that renders through `backend` without touching the
+    /// terminal mode
+    pub fn headless_with_backend(backend: B, nb_col: usize, nb_row: usize) -> Self {
+        Terminal::init(backend, (nb_col, nb_row), Mode::Headless, false)
+    }
+
+    fn init(backend: B, size: (usize, usize), mode: Mode, monochrome: bool) -> Self {
+        Terminal {
+            backend,
+            draw_buffer: Buffer::empty(size.0, size.1),
+            prev_buffer: Buffer::empty(size.0, size.1),
+            redraw: false,
+            size,
+            mode,
+            mouse: false,
+            bracketed_paste: false,
+            keyboard_enhancements: Vec::new(),
+            synchronized: true,
+            cursor_style: CursorStyle::Default,
+            cursor_visible: None,
+            color_depth: ColorDepth::TrueColor,
+            monochrome,
+            recording: None,
+        }
+    }
+
+    /// Present each frame atomically using synchronized updates, enabled by default. Terminals
+    /// without support ignore the sequences.
+    pub fn synchronized_update(mut self, enabled: bool) -> Self {
+        self.synchronized = enabled;
+        self
     }
 
     /// Obtains a difference between the previous and the current buffer and passes it to the
     /// current backend for drawing.
     pub fn apply_change(&mut self) -> io::Result<()> {
         let Terminal {
-            out, draw_buffer, ..
+            backend,
+            draw_buffer,
+            mode,
+            color_depth,
+            monochrome,
+            ..
         } = self;
-        let changes = self.prev_buffer.diff(draw_buffer);
-        Self::draw_changes(out, changes.into_iter())?;
+        if std::mem::take(&mut self.redraw) {
+            self.prev_buffer.reset();
+        }
+        let changes = self.prev_buffer.diff_with_clear(draw_buffer);
+        let pen = Pen::new(*color_depth).monochrome(*monochrome);
+        Self::draw_changes(backend, mode.origin(), pen, changes.into_iter())?;
         std::mem::swap(&mut self.draw_buffer, &mut self.prev_buffer);
+        // The previous frame forgets to the terminal default, not the base style
+        std::mem::swap(&mut self.draw_buffer.blank, &mut self.prev_buffer.blank);
         self.draw_buffer.reset();
         Ok(())
     }
 
     /// Queries the backend for size and resizes if it doesn't match the previous size.
     fn autoresize(&mut self) -> io::Result<()> {
-        let (w, h) = terminal::size().map(|(w, h)| (w as usize, h as usize))?;
+        if self.mode == Mode::Headless {
+            return Ok(());
+        }
+        let (w, mut h) = self.backend.size().map(|(w, h)| (w as usize, h as usize))?;
+        if let Mode::Inline { .. } = self.mode {
+            h = self.size.1;
+        }
         if (w, h) != self.size {
             self.size = (w, h);
             self.draw_buffer.resize(w, h);
             self.prev_buffer.resize(w, h);
             // Force a full redraw on next frame
-            queue!(self.out, Clear(ClearType::All))?;
-            self.prev_buffer.reset();
+            self.clear()?;
         }
         Ok(())
     }
 
-    pub fn suspend_ui(&mut self, f: impl FnOnce()) -> io::Result<()> {
-        disable_raw_mode()?;
-        execute!(self.out, LeaveAlternateScreen)?;
-        f();
-        enable_raw_mode()?;
-        execute!(self.out, EnterAlternateScreen)?;
-        self.prev_buffer.reset();
+    /// Clear the screen and forget the previous frame, taking effect on the next `draw` or
+    /// `apply_change` which repaints everything
+    pub fn clear(&mut self) -> io::Result<()> {
+        match self.mode {
+            Mode::Inline { origin } => {
+                self.backend.move_to(0, origin)?;
+                self.backend.clear(ClearType::FromCursorDown)?;
+            }
+            _ => self.backend.clear(ClearType::All)?,
+        }
+        self.force_redraw();
         Ok(())
     }
 
+    /// Forget the previous frame so that the next `draw` or `apply_change` paints every
+    /// non-blank cell instead of only the changed ones, cheaper than `clear`
+    pub fn force_redraw(&mut self) {
+        self.redraw = true;
+    }
+
+    /// Restore the normal terminal while running `f`, for example to spawn an editor, and
+    /// return its result. Mouse capture, bracketed paste, keyboard enhancements and the cursor
+    /// style are turned off meanwhile. The UI is set back up even if `f` or the teardown fails,
+    /// `f` not being run in the latter case.
+    pub fn suspend_ui<R>(&mut self, f: impl FnOnce() -> io::Result<R>) -> io::Result<R> {
+        if let Err(err) = self.pause_ui() {
+            self.resume_ui().ok();
+            return Err(err);
+        }
+        let result = f();
+        self.resume_ui()?;
+        result
+    }
+
+    /// Restore the normal terminal for `suspend_ui`
+    fn pause_ui(&mut self) -> io::Result<()> {
+        let out = &mut self.backend;
+        for _ in &self.keyboard_enhancements {
+            out.pop_keyboard_enhancement()?;
+        }
+        if self.bracketed_paste {
+            out.disable_bracketed_paste()?;
+        }
+        if self.mouse {
+            out.disable_mouse()?;
+        }
+        if self.cursor_style != CursorStyle::Default {
+            out.set_cursor_style(CursorStyle::Default)?;
+        }
+        match self.mode {
+            Mode::Fullscreen => {
+                out.disable_raw_mode()?;
+                out.leave_alternate_screen()?;
+            }
+            Mode::Inline { .. } => out.disable_raw_mode()?,
+            Mode::Headless => {}
+        }
+        out.flush()
+    }
+
+    /// Set the UI back up after `suspend_ui`, attempting every step and returning the first
+    /// error
+    fn resume_ui(&mut self) -> io::Result<()> {
+        self.redraw = true;
+        self.cursor_visible = None;
+        let out = &mut self.backend;
+        let mut steps = Vec::new();
+        match self.mode {
+            Mode::Fullscreen => {
+                steps.push(out.enable_raw_mode());
+                steps.push(out.enter_alternate_screen());
+            }
+            Mode::Inline { .. } => steps.push(out.enable_raw_mode()),
+            Mode::Headless => {}
+        }
+        if self.mouse {
+            steps.push(out.enable_mouse());
+        }
+        if self.bracketed_paste {
+            steps.push(out.enable_bracketed_paste());
+        }
+        for flags in &self.keyboard_enhancements {
+            steps.push(out.push_keyboard_enhancement(*flags));
+        }
+        // The suspended program may have changed the cursor shape
+        if self.cursor_style != CursorStyle::Default {
+            steps.push(out.set_cursor_style(self.cursor_style));
+        }
+        steps.push(out.flush());
+        steps.into_iter().collect()
+    }
+
     /// Synchronizes terminal size, calls the rendering closure, flushes the current internal state
     /// and prepares for the next draw call.
     pub fn draw<F>(&mut self, f: F) -> io::Result<()>
     where
         F: FnOnce(&mut Canvas),
     {
+        if self.synchronized {
+            self.backend.begin_synchronized_update()?;
+        }
+        let mut result = self.draw_frame(f);
+        if self.synchronized {
+            // Ended and flushed even on error, the terminal would stop refreshing otherwise
+            result = result.and(self.backend.end_synchronized_update());
+        }
+        result.and(self.backend.flush())
+    }
+
+    /// Draw a frame without flushing
+    fn draw_frame(&mut self, f: impl FnOnce(&mut Canvas)) -> io::Result<()> {
         self.autoresize()?;
         let buf = &mut self.draw_buffer;
         f(&mut buf.canvas());
+        if let Some(frames) = &mut self.recording {
+            frames.push(Frame(buf.clone()));
+        }
 
         let pos = buf.cursor_pos;
 
-        // Draw to stdout
+        // Draw to the backend
         self.apply_change()?;
 
-        match pos {
-            None => queue!(self.out, Hide)?,
-            Some((x, y)) => {
-                queue!(self.out, Show)?;
-                queue!(self.out, MoveTo(x as u16, y as u16))?;
+        // Visibility is only emitted on change to avoid cursor flicker
+        if self.cursor_visible != Some(pos.is_some()) {
+            match pos {
+                None => self.backend.hide_cursor()?,
+                Some(_) => self.backend.show_cursor()?,
             }
+            self.cursor_visible = Some(pos.is_some());
+        }
+        if let Some((x, y)) = pos {
+            self.backend
+                .move_to(x as u16, y as u16 + self.mode.origin())?;
+        }
+        Ok(())
+    }
+
+    /// Change the cursor shape, kept until changed again
+    pub fn set_cursor_style(&mut self, style: CursorStyle) -> io::Result<()> {
+        self.backend.set_cursor_style(style)?;
+        self.backend.flush()?;
+        self.cursor_style = style;
+        Ok(())
+    }
+
+    /// Downsample colors to what the terminal supports, true color by default. The next frame
+    /// is fully redrawn.
+    pub fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.color_depth = depth;
+        self.force_redraw();
+    }
+
+    /// Style of the cells left blank, for example to set the background of the whole UI
+    pub fn set_base_style(&mut self, style: Style) {
+        self.draw_buffer.set_base_style(style);
+        self.draw_buffer.reset();
+    }
+
+    /// Emit no colors while keeping text modifiers, enabled by default in real terminals when
+    /// `NO_COLOR` is set. The next frame is fully redrawn.
+    pub fn set_monochrome(&mut self, monochrome: bool) {
+        self.monochrome = monochrome;
+        self.force_redraw();
+    }
+
+    /// Start reporting mouse events
+    pub fn enable_mouse(&mut self) -> io::Result<()> {
+        self.backend.enable_mouse()?;
+        self.backend.flush()?;
+        self.mouse = true;
+        Ok(())
+    }
+
+    /// Stop reporting mouse events
+    pub fn disable_mouse(&mut self) -> io::Result<()> {
+        self.backend.disable_mouse()?;
+        self.backend.flush()?;
+        self.mouse = false;
+        Ok(())
+    }
+
+    /// Report pasted text as a single `Event::Paste` instead of key presses
+    pub fn enable_bracketed_paste(&mut self) -> io::Result<()> {
+        self.backend.enable_bracketed_paste()?;
+        self.backend.flush()?;
+        self.bracketed_paste = true;
+        Ok(())
+    }
+
+    /// Report pasted text as key presses again
+    pub fn disable_bracketed_paste(&mut self) -> io::Result<()> {
+        self.backend.disable_bracketed_paste()?;
+        self.backend.flush()?;
+        self.bracketed_paste = false;
+        Ok(())
+    }
+
+    /// Change how keys are reported using the kitty keyboard protocol, for example to tell a
+    /// lone Escape from the start of an escape sequence. Returns false without pushing when the
+    /// terminal reports no support, headless terminals always push. Terminals without support
+    /// ignore the sequence.
+    pub fn push_keyboard_enhancement(
+        &mut self,
+        flags: KeyboardEnhancementFlags,
+    ) -> io::Result<bool> {
+        if self.mode != Mode::Headless && !self.backend.supports_keyboard_enhancement()? {
+            return Ok(false);
         }
+        self.backend.push_keyboard_enhancement(flags)?;
+        self.backend.flush()?;
+        self.keyboard_enhancements.push(flags);
+        Ok(true)
+    }
+
+    /// Restore the key reporting in place before the last push, if any
+    pub fn pop_keyboard_enhancement(&mut self) -> io::Result<()> {
+        if self.keyboard_enhancements.pop().is_some() {
+            self.backend.pop_keyboard_enhancement()?;
+            self.backend.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Translate a mouse event position into buffer coordinates, if it falls inside the drawing
+    /// area
+    pub fn event_to_cell(&self, column: u16, row: u16) -> Option<(usize, usize)> {
+        let (x, y) = (
+            column as usize,
+            row.checked_sub(self.mode.origin())? as usize,
+        );
+        (x < self.size.0 && y < self.size.1).then_some((x, y))
+    }
+
+    /// Keep a copy of every frame drawn from now on
+    pub fn start_recording(&mut self) {
+        self.recording.get_or_insert_with(Vec::new);
+    }
+
+    /// Stop recording
+    pub fn stop_recording(&mut self) {
+        self.recording = None;
+    }
+
+    /// Frames recorded since recording started or the last call, recording goes on
+    pub fn take_recording(&mut self) -> Vec<Frame> {
+        self.recording
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Render the frame drawn by the last `draw` call as plain text
+    pub fn snapshot(&self) -> String {
+        // Buffers are swapped after each draw
+        self.prev_buffer.snapshot()
+    }
+
+    /// Cursor position set by the last `draw` call, None when hidden
+    pub fn cursor_pos(&self) -> Option<(usize, usize)> {
+        // Buffers are swapped after each draw
+        self.prev_buffer.cursor_pos
+    }
 
-        // Flush
-        self.out.flush()
+    /// Backend the terminal draws through
+    pub fn backend(&self) -> &B {
+        &self.backend
     }
 
-    fn draw_changes<'a, I>(out: &mut io::Stdout, content: I) -> io::Result<()>
+    /// Backend the terminal draws through, for operations the terminal does not expose
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    fn draw_changes<'a, I>(out: &mut B, origin: u16, mut pen: Pen, content: I) -> io::Result<()>
     where
-        I: Iterator<Item = (u16, u16, &'a Cell)>,
+        I: Iterator<Item = Change<'a>>,
     {
-        let mut colors = (Color::Reset, Color::Reset);
-        let mut modifier = Modifier::empty();
         let mut last_pos: Option<(u16, u16)> = None;
+        let mut link: Option<&str> = None;
 
-        queue!(
-            out,
-            SetForegroundColor(Color::Reset),
-            SetBackgroundColor(Color::Reset),
-            SetAttribute(Attribute::Reset)
-        )?;
+        // Resetting attributes also resets colors
+        out.set_attribute(Attribute::Reset)?;
 
-        for (x, y, cell) in content {
+        // Adjacent cells sharing the same style are printed at once
+        let mut run = String::new();
+        for change in content {
+            let (x, y) = change.pos();
+            let adjacent = matches!(last_pos, Some(p) if x == p.0 + 1 && y == p.1);
+            let extend = match change {
+                Change::Cell(_, _, cell) => {
+                    adjacent && pen.matches(cell) && cell.link.as_deref() == link
+                }
+                Change::ClearLine(..) => false,
+            };
+            if !extend && !run.is_empty() {
+                out.print(&run)?;
+                run.clear();
+            }
             // Move the cursor if the previous location was not (x - 1, y)
-            if !matches!(last_pos, Some(p) if x == p.0 + 1 && y == p.1) {
-                queue!(out, MoveTo(x, y))?;
+            if !adjacent {
+                out.move_to(x, y + origin)?;
             }
+            let cell = match change {
+                Change::Cell(_, _, cell) => cell,
+                Change::ClearLine(_, _, blank) => {
+                    // Clearing fills with the current background
+                    pen.apply(out, blank)?;
+                    out.clear(ClearType::UntilNewLine)?;
+                    last_pos = None;
+                    continue;
+                }
+            };
             last_pos = Some((x, y));
-            if cell.modifier != modifier {
-                Modifier::diff(out, modifier, cell.modifier)?;
-                modifier = cell.modifier;
-            }
-            let new = (cell.fg, cell.bg);
-            match (colors.0 == new.0, colors.1 == new.1) {
-                (false, false) => queue!(out, SetColors(Colors::new(new.0, new.1)))?,
-                (false, true) => queue!(out, SetForegroundColor(new.0))?,
-                (true, false) => queue!(out, SetBackgroundColor(new.1))?,
-                (true, true) => {}
+            pen.apply(out, cell)?;
+            if cell.link.as_deref() != link {
+                link = cell.link.as_deref();
+                out.set_link(link)?;
             }
-            colors = new;
-            queue!(out, Print(&cell.char))?;
+            run.push_str(&cell.symbol);
+        }
+        if !run.is_empty() {
+            out.print(&run)?;
+        }
+        if link.is_some() {
+            out.set_link(None)?;
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::style::none;
+
+    use super::*;
+
+    /// Headless terminal capturing its escape sequences
+    #[cfg(feature = "crossterm")]
+    fn ansi(nb_col: usize, nb_row: usize) -> Terminal<CrosstermBackend<Vec<u8>>> {
+        Terminal::headless(Vec::new(), nb_col, nb_row)
+    }
+
+    #[test]
+    fn headless_draw() {
+        let mut term = Terminal::test(5, 2);
+        term.draw(|c| {
+            c.line("hello", none()).line("world!", none());
+        })
+        .unwrap();
+        assert_eq!(term.snapshot(), "hello\nworld");
+
+        term.draw(|c| {
+            c.btm().draw("hi", none());
+        })
+        .unwrap();
+        assert_eq!(term.snapshot(), "\nhi");
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn headless_output() {
+        let mut term = ansi(5, 1);
+        term.draw(|c| {
+            c.line("hello", none());
+        })
+        .unwrap();
+        let out = String::from_utf8(std::mem::take(term.writer())).unwrap();
+        assert!(out.contains("hello"));
+
+        // Nothing changed
+        term.draw(|c| {
+            c.line("hello", none());
+        })
+        .unwrap();
+        let out = String::from_utf8(std::mem::take(term.writer())).unwrap();
+        assert!(!out.contains("hello"));
+    }
+
+    #[test]
+    fn cursor_pos() {
+        let mut term = Terminal::test(10, 2);
+        term.draw(|c| {
+            c.top();
+            c.top().draw("abc", none()).cursor();
+        })
+        .unwrap();
+        assert_eq!(term.cursor_pos(), Some((3, 1)));
+
+        term.draw(|_| {}).unwrap();
+        assert_eq!(term.cursor_pos(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn base_style() {
+        let mut term = ansi(4, 2);
+        term.set_base_style(none().bg(Color::Blue));
+        for _ in 0..2 {
+            term.draw(|c| {
+                c.line("hi", none().fg(Color::Red));
+            })
+            .unwrap();
+            let frame = &term.prev_buffer;
+            assert_eq!(frame.content[0].bg, Color::Blue);
+            assert_eq!(frame.content[0].fg, Color::Red);
+            assert!(frame.content[2..].iter().all(|c| c.bg == Color::Blue));
+        }
+
+        // Redraws paint the base style
+        term.force_redraw();
+        term.writer().clear();
+        term.draw(|_| {}).unwrap();
+        let out = String::from_utf8(std::mem::take(term.writer())).unwrap();
+        assert!(out.contains("\x1b[48;5;12m"));
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn mouse() {
+        let mut term = ansi(5, 2);
+        term.enable_mouse().unwrap();
+        assert!(term.writer().ends_with(b"\x1b[?1006h"));
+        term.disable_mouse().unwrap();
+        assert!(term.writer().ends_with(b"\x1b[?1000l"));
+
+        assert_eq!(term.event_to_cell(4, 1), Some((4, 1)));
+        assert_eq!(term.event_to_cell(5, 1), None);
+        assert_eq!(term.event_to_cell(0, 2), None);
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn bracketed_paste() {
+        let mut term = ansi(5, 1);
+        term.enable_bracketed_paste().unwrap();
+        assert!(term.writer().ends_with(b"\x1b[?2004h"));
+        term.disable_bracketed_paste().unwrap();
+        assert!(term.writer().ends_with(b"\x1b[?2004l"));
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn keyboard_enhancement() {
+        let mut term = ansi(5, 1);
+        let flags = KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+            | KeyboardEnhancementFlags::REPORT_EVENT_TYPES;
+        assert!(term.push_keyboard_enhancement(flags).unwrap());
+        assert!(term.writer().ends_with(b"\x1b[>3u"));
+        term.pop_keyboard_enhancement().unwrap();
+        assert!(term.writer().ends_with(b"\x1b[<1u"));
+
+        // Nothing left to pop
+        term.writer().clear();
+        term.pop_keyboard_enhancement().unwrap();
+        assert!(term.writer().is_empty());
+
+        // Not pushed without support
+        let mut term = Terminal::with_backend(Recorder::default()).unwrap();
+        assert!(!term.push_keyboard_enhancement(flags).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn cursor_visibility() {
+        let mut term = ansi(5, 1);
+        let mut hides = 0;
+        for _ in 0..2 {
+            term.draw(|_| {}).unwrap();
+            let out = String::from_utf8(std::mem::take(term.writer())).unwrap();
+            hides += out.matches("\x1b[?25l").count();
+        }
+        assert_eq!(hides, 1);
+
+        term.draw(|c| {
+            c.top().cursor();
+        })
+        .unwrap();
+        term.draw(|c| {
+            c.top().draw("a", none()).cursor();
+        })
+        .unwrap();
+        let out = String::from_utf8(std::mem::take(term.writer())).unwrap();
+        assert_eq!(out.matches("\x1b[?25h").count(), 1);
+        assert!(out.ends_with("\x1b[1;2H\x1b[?2026l"));
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn synchronized_update() {
+        let mut term = ansi(5, 1);
+        term.draw(|c| {
+            c.line("hello", none());
+        })
+        .unwrap();
+        assert!(term.writer().starts_with(b"\x1b[?2026h"));
+        assert!(term.writer().ends_with(b"\x1b[?2026l"));
+
+        let mut term = ansi(5, 1).synchronized_update(false);
+        term.draw(|c| {
+            c.line("hello", none());
+        })
+        .unwrap();
+        let out = String::from_utf8(std::mem::take(term.writer())).unwrap();
+        assert!(!out.contains("\x1b[?2026"));
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn cursor_style() {
+        let mut term = ansi(5, 1);
+        term.set_cursor_style(CursorStyle::SteadyBar).unwrap();
+        assert!(term.writer().ends_with(b"\x1b[6 q"));
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn suspend_ui() {
+        let mut term = ansi(5, 1);
+        assert_eq!(term.suspend_ui(|| Ok(42)).unwrap(), 42);
+
+        // Restored on error
+        term.set_cursor_style(CursorStyle::SteadyBar).unwrap();
+        term.writer().clear();
+        let result: io::Result<()> = term.suspend_ui(|| Err(io::Error::other("editor failed")));
+        assert_eq!(result.unwrap_err().to_string(), "editor failed");
+        assert_eq!(term.writer(), b"\x1b[0 q\x1b[6 q");
+
+        // Input modes are off while suspended
+        term.set_cursor_style(CursorStyle::Default).unwrap();
+        term.enable_mouse().unwrap();
+        term.enable_bracketed_paste().unwrap();
+        term.push_keyboard_enhancement(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+            .unwrap();
+        term.writer().clear();
+        term.suspend_ui(|| Ok(())).unwrap();
+        let out = String::from_utf8(std::mem::take(term.writer())).unwrap();
+        let (suspend, resume) = out.split_at(out.find("\x1b[?1000h").unwrap());
+        assert!(suspend.starts_with("\x1b[<1u\x1b[?2004l"));
+        assert!(suspend.contains("\x1b[?1000l"));
+        assert!(resume.contains("\x1b[?2004h\x1b[>1u"));
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn clear() {
+        let mut term = ansi(5, 1);
+        for _ in 0..2 {
+            term.draw(|c| {
+                c.line("hello", none());
+            })
+            .unwrap();
+        }
+        term.writer().clear();
+        term.clear().unwrap();
+        term.draw(|c| {
+            c.line("hello", none());
+        })
+        .unwrap();
+        let out = String::from_utf8(std::mem::take(term.writer())).unwrap();
+        assert!(out.contains("\x1b[2J"));
+        assert!(out.contains("hello"));
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn force_redraw() {
+        let mut term = ansi(5, 2);
+        let draw = |term: &mut Terminal<_>| {
+            term.draw(|c| {
+                c.btm().draw("hi", none()).cursor();
+            })
+            .unwrap();
+            String::from_utf8(std::mem::take(term.writer())).unwrap()
+        };
+        draw(&mut term);
+        assert!(!draw(&mut term).contains("hi"));
+
+        // The last frame is still known
+        term.force_redraw();
+        assert_eq!(term.snapshot(), "\nhi");
+        assert_eq!(term.cursor_pos(), Some((2, 1)));
+        assert!(draw(&mut term).contains("hi"));
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn single_flush_per_frame() {
+        #[derive(Default)]
+        struct FlushCount {
+            data: Vec<u8>,
+            flushes: usize,
+        }
+
+        impl io::Write for FlushCount {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.data.write(buf)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                self.flushes += 1;
+                Ok(())
+            }
+        }
+
+        let mut term = Terminal::headless(FlushCount::default(), 20, 10);
+        term.draw(|c| {
+            for _ in 0..10 {
+                c.line("abcdefghijklmnopqrst", none());
+            }
+        })
+        .unwrap();
+        assert_eq!(term.writer().flushes, 1);
+        assert!(String::from_utf8_lossy(&term.writer().data).contains("abcdefghijklmnopqrst"));
+    }
+
+    /// Backend recording printed text, of a fixed size
+    #[derive(Default)]
+    struct Recorder {
+        prints: Vec<String>,
+        raw: bool,
+        alternate: bool,
+        /// Fail to leave the alternate screen
+        stuck: bool,
+        /// Fail to print
+        broken: bool,
+        /// Inside a synchronized update
+        synchronized: bool,
+    }
+
+    impl Backend for Recorder {
+        fn move_to(&mut self, _: u16, _: u16) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn print(&mut self, text: &str) -> io::Result<()> {
+            if self.broken {
+                return Err(io::Error::other("broken"));
+            }
+            self.prints.push(text.to_string());
+            Ok(())
+        }
+
+        fn set_fg(&mut self, _: Color) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn set_bg(&mut self, _: Color) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn set_attribute(&mut self, _: Attribute) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn clear(&mut self, _: ClearType) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn show_cursor(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn hide_cursor(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn set_cursor_style(&mut self, _: CursorStyle) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn enter_alternate_screen(&mut self) -> io::Result<()> {
+            self.alternate = true;
+            Ok(())
+        }
+
+        fn leave_alternate_screen(&mut self) -> io::Result<()> {
+            if self.stuck {
+                return Err(io::Error::other("stuck"));
+            }
+            self.alternate = false;
+            Ok(())
+        }
+
+        fn enable_raw_mode(&mut self) -> io::Result<()> {
+            self.raw = true;
+            Ok(())
+        }
+
+        fn disable_raw_mode(&mut self) -> io::Result<()> {
+            self.raw = false;
+            Ok(())
+        }
+
+        fn enable_mouse(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn disable_mouse(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn begin_synchronized_update(&mut self) -> io::Result<()> {
+            self.synchronized = true;
+            Ok(())
+        }
+
+        fn end_synchronized_update(&mut self) -> io::Result<()> {
+            self.synchronized = false;
+            Ok(())
+        }
+
+        fn size(&self) -> io::Result<(u16, u16)> {
+            Ok((6, 2))
+        }
+
+        fn cursor_position(&mut self) -> io::Result<(u16, u16)> {
+            Ok((0, 0))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn coalesce_print() {
+        let mut buf = Buffer::empty(10, 1);
+        buf.canvas()
+            .top()
+            .draw("hello", none())
+            .draw("world", none().bold());
+        let mut out = Recorder::default();
+        let changes = Buffer::empty(10, 1).diff_with_clear(&buf);
+        Terminal::<Recorder>::draw_changes(
+            &mut out,
+            0,
+            Pen::new(ColorDepth::TrueColor),
+            changes.into_iter(),
+        )
+        .unwrap();
+        assert_eq!(out.prints, ["hello", "world"]);
+    }
+
+    #[test]
+    fn custom_backend() {
+        let mut term = Terminal::with_backend(Recorder::default()).unwrap();
+        assert!(term.backend().raw && term.backend().alternate);
+        term.draw(|c| {
+            c.line("hello world", none());
+        })
+        .unwrap();
+        // Sized by the backend
+        assert_eq!(term.snapshot(), "hello\n");
+        assert_eq!(term.backend().prints, ["hello"]);
+
+        term.suspend_ui(|| Ok(())).unwrap();
+        assert!(term.backend().raw && term.backend().alternate);
+
+        // Set back up after a failed teardown, without running the closure
+        term.backend_mut().stuck = true;
+        let result = term.suspend_ui(|| -> io::Result<()> { unreachable!() });
+        assert_eq!(result.unwrap_err().to_string(), "stuck");
+        assert!(term.backend().raw && term.backend().alternate);
+        term.backend_mut().stuck = false;
+
+        // Synchronized update ended on error
+        term.backend_mut().broken = true;
+        let result = term.draw(|c| {
+            c.line("bye", none());
+        });
+        assert_eq!(result.unwrap_err().to_string(), "broken");
+        assert!(!term.backend().synchronized);
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn attribute_order() {
+        let mut term = ansi(2, 1).synchronized_update(false);
+        term.draw(|c| {
+            c.top()
+                .draw("a", none())
+                .draw("b", none().fg(Color::Red).bg(Color::Blue).bold());
+        })
+        .unwrap();
+        let out = String::from_utf8(std::mem::take(term.writer())).unwrap();
+        assert_eq!(
+            out,
+            "\x1b[0m\x1b[1;1Ha\x1b[1m\x1b[38;5;9;48;5;12mb\x1b[?25l"
+        );
+    }
+
+    #[test]
+    fn recording() {
+        let mut term = Terminal::test(3, 1);
+        term.draw(|c| {
+            c.line("a", none());
+        })
+        .unwrap();
+        term.start_recording();
+        for s in ["b", "c", "d"] {
+            term.draw(|c| {
+                c.line(s, none());
+            })
+            .unwrap();
+        }
+        let frames = term.take_recording();
+        assert_eq!(frames.len(), 3);
+        let snapshots: Vec<_> = frames.iter().map(Frame::snapshot).collect();
+        assert_eq!(snapshots, ["b", "c", "d"]);
+        assert_eq!(
+            frames[0].cell_at(0, 0),
+            Some(("b", none().fg(Color::Reset).bg(Color::Reset)))
+        );
+
+        term.stop_recording();
+        term.draw(|_| {}).unwrap();
+        assert!(term.take_recording().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn color_depth() {
+        let mut term = ansi(1, 1);
+        term.set_color_depth(ColorDepth::Ansi256);
+        term.draw(|c| {
+            c.line("a", none().fg(crate::rgb(255, 0, 0)));
+        })
+        .unwrap();
+        let out = String::from_utf8(std::mem::take(term.writer())).unwrap();
+        assert!(out.contains("\x1b[38;5;196ma"));
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn monochrome() {
+        let mut term = ansi(5, 1);
+        term.set_monochrome(true);
+        term.draw(|c| {
+            c.top()
+                .draw("ab", none().fg(Color::Red).bg(Color::Blue))
+                .draw("c", none().bold().fg(crate::rgb(1, 2, 3)));
+        })
+        .unwrap();
+        let out = String::from_utf8(std::mem::take(term.writer())).unwrap();
+        assert!(out.contains("ab\x1b[1mc"));
+        assert!(!out.contains("38;") && !out.contains("48;"));
+    }
+
+    #[test]
+    #[cfg(feature = "crossterm")]
+    fn hyperlink() {
+        let mut term = ansi(10, 1);
+        term.draw(|c| {
+            c.top()
+                .draw("see:", none())
+                .draw_link("docs", "https://example.com", none())
+                .draw("!", none());
+        })
+        .unwrap();
+        let out = String::from_utf8(std::mem::take(term.writer())).unwrap();
+        assert!(out.contains("see:\x1b]8;;https://example.com\x1b\\docs\x1b]8;;\x1b\\!"));
+    }
+}
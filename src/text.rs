@@ -0,0 +1,64 @@
+use super::{str_width, style::Style};
+
+/// A line of styled texts
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Spans(pub Vec<(String, Style)>);
+
+impl Spans {
+    /// Append a styled text
+    pub fn span(mut self, text: impl Into<String>, style: Style) -> Self {
+        self.0.push((text.into(), style));
+        self
+    }
+
+    /// Printed width of all the texts
+    pub fn width(&self) -> usize {
+        self.0.iter().map(|(text, _)| str_width(text)).sum()
+    }
+}
+
+/// Multiple lines of styled texts
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Text(pub Vec<Spans>);
+
+impl Text {
+    /// Append a line
+    pub fn line(mut self, spans: Spans) -> Self {
+        self.0.push(spans);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        buffer::Buffer,
+        style::{none, Color},
+    };
+
+    use super::*;
+
+    #[test]
+    fn draw_text() {
+        let (red, blue) = (none().fg(Color::Red), none().fg(Color::Blue));
+        let text = Text::default()
+            .line(Spans::default().span("ab", red).span("c", blue))
+            .line(Spans::default().span("コ", blue).span("d", red));
+        assert_eq!(text.0[0].width(), 3);
+        assert_eq!(text.0[1].width(), 3);
+
+        let mut buffer = Buffer::empty(4, 3);
+        buffer.canvas().draw_text(&text);
+        assert_eq!(buffer.snapshot(), "abc\nコd\n");
+        let colors: Vec<_> = buffer.content.iter().map(|c| c.fg).collect();
+        assert_eq!(
+            colors,
+            [
+                [Color::Red, Color::Red, Color::Blue, Color::Reset],
+                [Color::Blue, Color::Reset, Color::Red, Color::Reset],
+                [Color::Reset; 4]
+            ]
+            .concat()
+        );
+    }
+}
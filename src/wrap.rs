@@ -0,0 +1,125 @@
+use unicode_segmentation::{UWordBounds, UnicodeSegmentation};
+
+use super::{split_at_width, str_width};
+
+/// Number of lines a text takes once wrapped in `width` columns, matching `Canvas::wrap`
+pub fn wrapped_height(text: &str, width: usize) -> usize {
+    WrapLines::new(text, width).count()
+}
+
+/// Iterator over the lines of a wrapped text, as the list of segments to draw on each line
+pub(crate) struct WrapLines<'a> {
+    words: UWordBounds<'a>,
+    next: Option<&'a str>,
+    wrapped: bool,
+    width: usize,
+}
+
+impl<'a> WrapLines<'a> {
+    pub fn new(text: &'a str, width: usize) -> Self {
+        let mut words = text.split_word_bounds();
+        WrapLines {
+            next: words.next(),
+            words,
+            wrapped: false,
+            width,
+        }
+    }
+
+    /// Change the width of the next lines
+    pub fn set_width(&mut self, width: usize) {
+        self.width = width;
+    }
+
+    /// Whether the last line returned ends a paragraph, at a newline or the end of the text
+    pub fn ends_paragraph(&self) -> bool {
+        !self.wrapped || self.next.is_none()
+    }
+}
+
+impl<'a> Iterator for WrapLines<'a> {
+    type Item = Vec<&'a str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next?;
+        self.wrapped = true;
+        let mut line = Vec::new();
+        let mut used = 0;
+        while let Some(word) = self.next {
+            let width = str_width(word);
+            if matches!(word, "\n" | "\r\n") {
+                self.next = self.words.next();
+                self.wrapped = false;
+                break;
+            } else if used + width <= self.width {
+                line.push(word);
+                used += width;
+                self.next = self.words.next();
+            } else {
+                if used == 0 {
+                    // Hard break a word longer than the line, always consuming a grapheme so
+                    // that a grapheme wider than the line cannot stall
+                    let (head, tail) = match split_at_width(word, self.width) {
+                        ("", _) => word.split_at(word.graphemes(true).next().unwrap().len()),
+                        split => split,
+                    };
+                    line.push(head);
+                    self.next = if tail.is_empty() {
+                        self.words.next()
+                    } else {
+                        Some(tail)
+                    };
+                }
+                break;
+            }
+        }
+        if self.wrapped {
+            // Don't start a wrapped line with whitespace, nor with a newline as it would leave
+            // a blank line
+            while self
+                .next
+                .is_some_and(|w| w.trim().is_empty() && !w.contains('\n'))
+            {
+                self.next = self.words.next();
+            }
+            if self.next.is_some_and(|w| matches!(w, "\n" | "\r\n")) {
+                self.next = self.words.next();
+                self.wrapped = false;
+            }
+        }
+        Some(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{buffer::Buffer, style::none};
+
+    use super::*;
+
+    #[test]
+    fn wrapped_height_match_wrap() {
+        for text in [
+            "",
+            "ab cd ef",
+            "12345 12345",
+            "abcdefghijklmnopqrst",
+            "a\n\nb\n",
+            "see abcdefghijk ok",
+            "コンピュータ ab コ",
+            "abc \nd",
+            "abc \n\nd",
+        ] {
+            for width in [1, 3, 5, 8] {
+                let mut buffer = Buffer::empty(width, 50);
+                let lines = buffer.canvas().wrap(text, none());
+                assert_eq!(wrapped_height(text, width), lines, "{text:?} in {width}");
+            }
+        }
+        assert_eq!(wrapped_height("ab cd ef", 5), 2);
+        assert_eq!(wrapped_height("コ", 0), 1);
+        // Whitespace filling the line up to a newline
+        assert_eq!(wrapped_height("abc \nd", 3), 2);
+        assert_eq!(wrapped_height("abc  \r\n\nd", 3), 3);
+    }
+}